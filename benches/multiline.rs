@@ -0,0 +1,37 @@
+//! Reading a record with thousands of embedded newlines takes time linear in its size.
+//! Run with `cargo bench --bench multiline`, time per byte should stay the same for all sizes.
+
+use std::{hint::black_box, time::Instant};
+
+/// Document with a single record which quoted field spans `lines` lines
+fn document(lines: usize) -> String {
+    let mut csv = String::from("id,text\r\n1,\"");
+    for _ in 0..lines {
+        csv.push_str("a line of the long quoted field\n");
+    }
+    csv.push_str("\"\r\n");
+    csv
+}
+
+fn main() {
+    const ROUNDS: u32 = 10;
+    println!("{:>8} {:>12} {:>12}", "lines", "ms", "ns/byte");
+    for lines in [1_000, 2_000, 4_000, 8_000, 16_000, 32_000] {
+        let csv = document(lines);
+        let start = Instant::now();
+        for _ in 0..ROUNDS {
+            let mut reader = justcsv::CsvReader::new(black_box(csv.as_bytes()));
+            let mut record = justcsv::Record::new();
+            while reader.read_record(&mut record).unwrap() {
+                black_box(&record);
+            }
+        }
+        let elapsed = start.elapsed() / ROUNDS;
+        println!(
+            "{:>8} {:>12.3} {:>12.2}",
+            lines,
+            elapsed.as_secs_f64() * 1e3,
+            elapsed.as_nanos() as f64 / csv.len() as f64
+        );
+    }
+}
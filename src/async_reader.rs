@@ -0,0 +1,124 @@
+use crate::{CsvPushParser, CsvReaderConfig, Headers, Position, Record};
+use futures_core::Stream;
+use std::{
+    pin::Pin,
+    sync::Arc,
+    task::{Context, Poll, ready},
+};
+use tokio::io::AsyncBufRead;
+
+/// Asynchronous CSV reader, it is a [`Stream`] of [records](Record) which may be accessed by header names.
+/// Chunks of the source are fed to [`CsvPushParser`], so the reader has the same [options](CsvReaderConfig)
+/// as [`CsvReader`](crate::CsvReader).
+///
+/// # Example
+///
+/// ```
+/// # tokio::runtime::Builder::new_current_thread().build().unwrap().block_on(async {
+/// let buf = "name,amount\r\nmilk,1.5".as_bytes();
+/// let config = justcsv::CsvReaderConfig::default().has_headers(true);
+/// let mut reader = justcsv::AsyncCsvReader::with_config(buf, config);
+/// let mut record = justcsv::Record::new();
+/// assert!(reader.read_record(&mut record).await.unwrap());
+/// assert_eq!("1.5", &record["amount"]);
+/// assert!(!reader.read_record(&mut record).await.unwrap());
+/// # });
+/// ```
+pub struct AsyncCsvReader<R> {
+    source: R,
+    parser: CsvPushParser,
+    /// The source is exhausted
+    eof: bool,
+}
+
+impl<R: AsyncBufRead + Unpin> AsyncCsvReader<R> {
+    /// Create reader with default options
+    pub fn new(source: R) -> Self {
+        Self::with_config(source, Default::default())
+    }
+
+    /// Create reader with options passed as [config](CsvReaderConfig)
+    pub fn with_config(source: R, config: CsvReaderConfig) -> Self {
+        Self {
+            source,
+            parser: CsvPushParser::with_config(config),
+            eof: false,
+        }
+    }
+
+    /// Returns CSV headers if they are expected and read already
+    pub fn headers(&self) -> Option<&[String]> {
+        self.parser.headers()
+    }
+
+    /// Returns CSV headers with index of columns by name, it is shared with records the reader yields
+    pub fn header_index(&self) -> Option<&Arc<Headers>> {
+        self.parser.header_index()
+    }
+
+    /// Lines skipped at the start of the stream, see [`CsvReader::preamble`](crate::CsvReader::preamble)
+    pub fn preamble(&self) -> &[String] {
+        self.parser.preamble()
+    }
+
+    /// Comment lines read so far, see [`CsvReader::comments`](crate::CsvReader::comments)
+    pub fn comments(&self) -> &[String] {
+        self.parser.comments()
+    }
+
+    /// Position of the next record in the stream
+    pub fn position(&self) -> Position {
+        self.parser.position()
+    }
+
+    /// Read next record into `record` reusing its memory.
+    /// Returns `false` if the stream is complete.
+    pub async fn read_record(&mut self, record: &mut Record) -> crate::Result<bool> {
+        std::future::poll_fn(|cx| self.poll_read_record(cx, record)).await
+    }
+
+    fn poll_read_record(
+        &mut self,
+        cx: &mut Context<'_>,
+        record: &mut Record,
+    ) -> Poll<crate::Result<bool>> {
+        loop {
+            match self.parser.read_record(record) {
+                Ok(false) if !self.eof => {}
+                result => return Poll::Ready(result),
+            }
+            let chunk = match ready!(Pin::new(&mut self.source).poll_fill_buf(cx)) {
+                Ok(chunk) => chunk,
+                Err(source) => {
+                    return Poll::Ready(Err(crate::Error::ReadFailed {
+                        position: self.parser.position(),
+                        source,
+                    }));
+                }
+            };
+            if chunk.is_empty() {
+                self.eof = true;
+                self.parser.finish();
+            } else {
+                let n = chunk.len();
+                self.parser.feed(chunk);
+                Pin::new(&mut self.source).consume(n);
+            }
+        }
+    }
+}
+
+impl<R: AsyncBufRead + Unpin> Stream for AsyncCsvReader<R> {
+    type Item = crate::Result<Record>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let mut record = Record::new();
+        Poll::Ready(
+            match ready!(self.get_mut().poll_read_record(cx, &mut record)) {
+                Ok(true) => Some(Ok(record)),
+                Ok(false) => None,
+                Err(e) => Some(Err(e)),
+            },
+        )
+    }
+}
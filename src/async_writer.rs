@@ -0,0 +1,160 @@
+use crate::{CsvWriter, CsvWriterConfig};
+use serde::Serialize;
+use std::fmt::Display;
+use tokio::io::{AsyncWrite, AsyncWriteExt};
+
+/// Asynchronous CSV writer. Rows are formatted by [`CsvWriter`], so the writer has the same
+/// [options](CsvWriterConfig) and escaping rules.
+/// Output is buffered, [flush](AsyncCsvWriter::flush) the writer before dropping it, otherwise the rest
+/// of the output is lost and reported with respect to [config](CsvWriterConfig::on_drop).
+///
+/// # Example
+///
+/// ```
+/// # tokio::runtime::Builder::new_current_thread().build().unwrap().block_on(async {
+/// let mut buf = Vec::new();
+/// let mut writer = justcsv::AsyncCsvWriter::new(&mut buf);
+/// writer.write_row(["1", "2", "3"]).await.unwrap();
+/// writer.write_row(["4", "5\"abc\"X", "6"]).await.unwrap();
+/// writer.flush().await.unwrap();
+/// drop(writer);
+/// assert_eq!(b"1,2,3\r\n4,\"5\"\"abc\"\"X\",6", buf.as_slice());
+/// # });
+/// ```
+pub struct AsyncCsvWriter<W: AsyncWrite + Unpin> {
+    /// It is taken only by `into_inner`
+    dest: Option<W>,
+    /// Formats rows into its buffer, the buffer is moved to `dest` when it is full
+    writer: CsvWriter<Vec<u8>>,
+}
+
+impl<W: AsyncWrite + Unpin> AsyncCsvWriter<W> {
+    /// Creates a CSV writer with default options
+    pub fn new(dest: W) -> Self {
+        Self::with_config(dest, Default::default())
+    }
+
+    /// Creates a CSV writer with options passed as [config](CsvWriterConfig)
+    pub fn with_config(dest: W, config: CsvWriterConfig) -> Self {
+        Self {
+            dest: Some(dest),
+            writer: CsvWriter::with_config(Vec::new(), config),
+        }
+    }
+
+    /// Save next row of comma separated values, see [`CsvWriter::write_row`]
+    pub async fn write_row<Field: AsRef<str>>(
+        &mut self,
+        row: impl AsRef<[Field]>,
+    ) -> crate::Result<()> {
+        self.writer.write_row(row)?;
+        self.write_formatted().await
+    }
+
+    /// Save next row of fields of any `Display` type, see [`CsvWriter::write_fields`]
+    pub async fn write_fields(
+        &mut self,
+        fields: impl IntoIterator<Item = impl Display>,
+    ) -> crate::Result<()> {
+        self.writer.write_fields(fields)?;
+        self.write_formatted().await
+    }
+
+    /// Append a field to the current record, see [`CsvWriter::write_field`]
+    pub fn write_field(&mut self, field: impl Display) -> crate::Result<()> {
+        self.writer.write_field(field)
+    }
+
+    /// Complete the record started by [`AsyncCsvWriter::write_field`]
+    pub async fn end_record(&mut self) -> crate::Result<()> {
+        self.writer.end_record()?;
+        self.write_formatted().await
+    }
+
+    /// Save CSV headers, see [`CsvWriter::write_headers`]
+    pub async fn write_headers(&mut self, headers: &[impl AsRef<str>]) -> crate::Result<()> {
+        self.writer.write_headers(headers)?;
+        self.write_formatted().await
+    }
+
+    /// Save any `serde::Serialize` value as a record, see [`CsvWriter::serialize`]
+    pub async fn serialize<T: Serialize + ?Sized>(&mut self, record: &T) -> crate::Result<()> {
+        self.writer.serialize(record)?;
+        self.write_formatted().await
+    }
+
+    /// Save whole CSV document
+    pub async fn write_document<Field: AsRef<str>, Record: AsRef<[Field]>>(
+        &mut self,
+        doc: &[Record],
+    ) -> crate::Result<()> {
+        for row in doc.iter() {
+            self.write_row(row).await?;
+        }
+        Ok(())
+    }
+
+    /// Write buffered output and flush the destination
+    pub async fn flush(&mut self) -> crate::Result<()> {
+        self.writer.flush()?;
+        self.write_formatted().await?;
+        Ok(self.dest_mut().flush().await?)
+    }
+
+    /// Flush the writer and unwrap the destination
+    pub async fn into_inner(mut self) -> crate::Result<W> {
+        self.flush().await?;
+        Ok(self
+            .dest
+            .take()
+            .expect("destination is present until into_inner"))
+    }
+
+    /// Reference to the destination, buffered output is not written into it yet
+    pub fn get_ref(&self) -> &W {
+        self.dest
+            .as_ref()
+            .expect("destination is present until into_inner")
+    }
+
+    /// Mutable reference to the destination, buffered output is not written into it yet
+    pub fn get_mut(&mut self) -> &mut W {
+        self.dest_mut()
+    }
+
+    fn dest_mut(&mut self) -> &mut W {
+        self.dest
+            .as_mut()
+            .expect("destination is present until into_inner")
+    }
+
+    /// Move output which the formatting writer has flushed into the destination
+    async fn write_formatted(&mut self) -> crate::Result<()> {
+        let formatted = self.writer.get_mut();
+        if formatted.is_empty() {
+            return Ok(());
+        }
+        let dest = self
+            .dest
+            .as_mut()
+            .expect("destination is present until into_inner");
+        let result = dest.write_all(formatted).await;
+        formatted.clear();
+        Ok(result?)
+    }
+}
+
+impl<W: AsyncWrite + Unpin> Drop for AsyncCsvWriter<W> {
+    fn drop(&mut self) {
+        if self.dest.is_none() {
+            return;
+        }
+        // the output cannot be written without awaiting, so it is lost
+        let lost = self.writer.get_ref().len() + self.writer.buffered();
+        if lost > 0 {
+            let message = format!("{lost} bytes of CSV output are not flushed before drop");
+            self.writer
+                .report_drop(std::io::Error::other(message).into());
+        }
+    }
+}
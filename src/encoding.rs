@@ -0,0 +1,209 @@
+use encoding_rs::{CoderResult, Encoding, UTF_8, UTF_16BE, UTF_16LE};
+use std::io::{BufRead, Read};
+
+/// Encoding of the CSV source
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum SourceEncoding {
+    /// UTF-8 as is, byte order mark is not treated specially
+    #[default]
+    Utf8,
+    /// Detect encoding by byte order mark (UTF-8, UTF-16LE or UTF-16BE),
+    /// use the given encoding if there is no BOM
+    Detect(&'static Encoding),
+    /// Source is in the given encoding, its BOM is skipped if present
+    Fixed(&'static Encoding),
+}
+
+/// Source adapter which yields UTF-8 content of the underlying source.
+/// UTF-8 content is passed as is, so invalid sequences are left for the parser to report.
+pub(crate) struct Decoded<R> {
+    inner: R,
+    /// `Some` until the BOM is sniffed, contains fallback encoding
+    detect: Option<&'static Encoding>,
+    decoder: Option<encoding_rs::Decoder>,
+    /// The decoder is flushed at the end of the source, it must not be used after that
+    finished: bool,
+    buf: Vec<u8>,
+    pos: usize,
+}
+
+impl<R: BufRead> Decoded<R> {
+    pub fn new(inner: R, encoding: SourceEncoding) -> Self {
+        let (detect, decoder) = match encoding {
+            SourceEncoding::Utf8 => (None, None),
+            SourceEncoding::Detect(encoding) => (Some(encoding), None),
+            SourceEncoding::Fixed(encoding) if encoding == UTF_8 => (Some(UTF_8), None),
+            SourceEncoding::Fixed(encoding) => {
+                (None, Some(encoding.new_decoder_with_bom_removal()))
+            }
+        };
+        Self {
+            inner,
+            detect,
+            decoder,
+            finished: false,
+            buf: Vec::new(),
+            pos: 0,
+        }
+    }
+
+    /// Mutable reference to the underlying source
+    pub fn get_mut(&mut self) -> &mut R {
+        &mut self.inner
+    }
+
+    /// Sniffed bytes are kept in `buf` if the source fails, so sniffing is resumed by the next read
+    fn sniff(&mut self, fallback: &'static Encoding) -> std::io::Result<()> {
+        while self.buf.len() < 3 {
+            let chunk = self.inner.fill_buf()?;
+            if chunk.is_empty() {
+                break;
+            }
+            let n = chunk.len().min(3 - self.buf.len());
+            self.buf.extend_from_slice(&chunk[..n]);
+            self.inner.consume(n);
+        }
+        let raw = std::mem::take(&mut self.buf);
+        let (encoding, bom_len) = Encoding::for_bom(&raw).unwrap_or((fallback, 0));
+        let raw = &raw[bom_len..];
+        if encoding == UTF_8 {
+            self.buf.extend_from_slice(raw);
+        } else {
+            let mut decoder = encoding.new_decoder_without_bom_handling();
+            decode(&mut decoder, raw, &mut self.buf, false);
+            self.decoder = Some(decoder);
+        }
+        Ok(())
+    }
+}
+
+/// Decode `src` appending to `dst`, returns number of bytes read from `src`
+fn decode(decoder: &mut encoding_rs::Decoder, src: &[u8], dst: &mut Vec<u8>, last: bool) -> usize {
+    let mut read = 0;
+    loop {
+        let max_len = decoder
+            .max_utf8_buffer_length(src.len() - read)
+            .unwrap_or(4 * (src.len() - read) + 16);
+        let start = dst.len();
+        dst.resize(start + max_len, 0);
+        let (result, n, written, _) = decoder.decode_to_utf8(&src[read..], &mut dst[start..], last);
+        dst.truncate(start + written);
+        read += n;
+        if result == CoderResult::InputEmpty {
+            break read;
+        }
+    }
+}
+
+impl<R: BufRead> Read for Decoded<R> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        let available = self.fill_buf()?;
+        let n = available.len().min(buf.len());
+        buf[..n].copy_from_slice(&available[..n]);
+        self.consume(n);
+        Ok(n)
+    }
+}
+
+impl<R: BufRead> BufRead for Decoded<R> {
+    fn fill_buf(&mut self) -> std::io::Result<&[u8]> {
+        if let Some(fallback) = self.detect {
+            self.sniff(fallback)?;
+            self.detect = None;
+        }
+        if self.pos < self.buf.len() {
+            return Ok(&self.buf[self.pos..]);
+        }
+        let Some(decoder) = self.decoder.as_mut() else {
+            return self.inner.fill_buf();
+        };
+        self.buf.clear();
+        self.pos = 0;
+        while self.buf.is_empty() && !self.finished {
+            let chunk = self.inner.fill_buf()?;
+            let last = chunk.is_empty();
+            let n = decode(decoder, chunk, &mut self.buf, last);
+            self.inner.consume(n);
+            self.finished = last;
+        }
+        Ok(&self.buf)
+    }
+
+    fn consume(&mut self, amt: usize) {
+        if self.pos < self.buf.len() {
+            self.pos += amt;
+        } else {
+            self.inner.consume(amt);
+        }
+    }
+}
+
+/// Converts UTF-8 output of the writer into the target encoding
+pub(crate) struct Encoder {
+    encoding: &'static Encoding,
+    encoder: Option<encoding_rs::Encoder>,
+    bom: bool,
+}
+
+impl Encoder {
+    pub fn new(encoding: &'static Encoding, bom: bool) -> Self {
+        let encoder = (encoding != UTF_8 && encoding != UTF_16LE && encoding != UTF_16BE)
+            .then(|| encoding.new_encoder());
+        Self {
+            encoding,
+            encoder,
+            bom,
+        }
+    }
+
+    /// Encode `text` appending to `dst`, byte order mark is prepended to the first output if needed
+    pub fn encode(&mut self, text: &str, dst: &mut Vec<u8>) -> crate::Result<()> {
+        if self.bom {
+            dst.extend_from_slice(match self.encoding {
+                e if e == UTF_8 => b"\xEF\xBB\xBF",
+                e if e == UTF_16LE => b"\xFF\xFE",
+                e if e == UTF_16BE => b"\xFE\xFF",
+                _ => b"",
+            });
+        }
+        match self.encoder.as_mut() {
+            None if self.encoding == UTF_16LE => {
+                dst.extend(text.encode_utf16().flat_map(u16::to_le_bytes));
+            }
+            None if self.encoding == UTF_16BE => {
+                dst.extend(text.encode_utf16().flat_map(u16::to_be_bytes));
+            }
+            None => dst.extend_from_slice(text.as_bytes()),
+            Some(encoder) => {
+                let mut read = 0;
+                loop {
+                    let rest = &text[read..];
+                    let max_len = encoder
+                        .max_buffer_length_from_utf8_without_replacement(rest.len())
+                        .unwrap_or(4 * rest.len() + 16);
+                    let start = dst.len();
+                    dst.resize(start + max_len, 0);
+                    let (result, n, written) = encoder.encode_from_utf8_without_replacement(
+                        rest,
+                        &mut dst[start..],
+                        false,
+                    );
+                    dst.truncate(start + written);
+                    read += n;
+                    match result {
+                        encoding_rs::EncoderResult::InputEmpty => break,
+                        encoding_rs::EncoderResult::OutputFull => continue,
+                        encoding_rs::EncoderResult::Unmappable(c) => {
+                            return Err(crate::Error::Unencodable {
+                                character: c,
+                                encoding: self.encoding.name(),
+                            });
+                        }
+                    }
+                }
+            }
+        }
+        self.bom = false;
+        Ok(())
+    }
+}
@@ -1,3 +1,4 @@
+use crate::{Limit, Position};
 use derive_more::From;
 
 /// Crate `Result` type alias
@@ -13,18 +14,184 @@ pub enum Error {
     /// Some IO fail
     #[from]
     Io(std::io::Error),
-    /// `nom` parsing fail
-    #[from]
-    Nom(nom::error::Error<&'static str>),
     // -- Internals
     /// Basically is a normal completion
     StreamComplete,
-    /// Stream ended but no correct CSV parsed
-    UnexpectedEof,
     /// User calls for headers to write but there were some records written into stream
     WriteHeadersAfterRecords,
-    /// Owned variant of a `nom` error
-    NomFailed(String),
+    /// Value cannot be serialized into a CSV record
+    Serialize(String),
+    /// Character cannot be represented in the output encoding
+    Unencodable {
+        /// Unmappable character
+        character: char,
+        /// Name of the output encoding
+        encoding: &'static str,
+    },
+    /// Field must be quoted but [`QuoteStyle::Never`](crate::QuoteStyle::Never) forbids it
+    QuoteRequired {
+        /// Index of the field in the row
+        column: usize,
+        /// Text of the field
+        field: String,
+    },
+    /// Field may be executed as a formula by spreadsheets, see [`Sanitize::Reject`](crate::Sanitize::Reject)
+    FormulaInjection {
+        /// Index of the field in the row
+        column: usize,
+        /// Text of the field
+        field: String,
+    },
+    /// Row has number of fields different from the headers, see [`CsvWriterConfig::field_count`](crate::CsvWriterConfig::field_count)
+    RowLengthMismatch {
+        /// Index of the row in the stream, headers are row 0
+        row: usize,
+        /// Number of fields the row should have
+        expected: usize,
+        /// Number of fields the row has
+        actual: usize,
+    },
+    // -- Reading records
+    /// Stream ended inside the quoted field of the record starting at the position
+    UnterminatedQuote(Position),
+    /// Closing quote is followed by something other than a separator or end of line,
+    /// position points to the failed field
+    GarbageAfterQuote(Position),
+    /// Record contains bytes which are not UTF-8, position points to the first invalid byte
+    InvalidUtf8(Position),
+    /// Record has unexpected number of fields
+    FieldCountMismatch {
+        /// Start of the record
+        position: Position,
+        /// Number of fields the record should have
+        expected: usize,
+        /// Number of fields the record has
+        actual: usize,
+    },
+    /// Header name is empty or duplicate
+    InvalidHeader {
+        /// Start of the headers
+        position: Position,
+        /// Index of the failed header
+        column: usize,
+        /// Failed header name
+        name: String,
+    },
+    /// Record cannot be deserialized into the user type
+    Deserialize {
+        /// Start of the record
+        position: Position,
+        /// Index of the failed field if the failure is caused by some field
+        field: Option<usize>,
+        /// Header of the failed field if there are headers
+        header: Option<String>,
+        /// What went wrong
+        message: String,
+    },
+    /// Record has no field with the index or header name, see [`Record::parse`](crate::Record::parse)
+    MissingField {
+        /// Start of the record if it is read by a reader
+        position: Option<Position>,
+        /// Index or header name of the field
+        field: String,
+    },
+    /// Field cannot be converted into the requested type, see [`Record::parse`](crate::Record::parse)
+    /// and [`Schema`](crate::Schema)
+    Convert {
+        /// Start of the record if it is read by a reader
+        position: Option<Position>,
+        /// Index of the field
+        column: usize,
+        /// Header or schema name of the field
+        header: Option<String>,
+        /// Raw text of the field
+        text: String,
+        /// What went wrong
+        message: String,
+    },
+    /// Record exceeds a resource limit of the reader. The rest of the record is scanned up to its end
+    /// without keeping it, then reading goes on with the next record
+    LimitExceeded {
+        /// Start of the record
+        position: Position,
+        /// Exceeded limit
+        limit: Limit,
+        /// Value of the limit
+        max: usize,
+    },
+    /// Source failed while reading the record starting at `position`
+    ReadFailed {
+        /// Start of the record being read
+        position: Position,
+        /// IO error of the source
+        source: std::io::Error,
+    },
+}
+
+/// Kind of the crate [`Error`] without any details
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ErrorKind {
+    /// See [`Error::Custom`]
+    Custom,
+    /// See [`Error::Io`] and [`Error::ReadFailed`]
+    Io,
+    /// See [`Error::StreamComplete`]
+    StreamComplete,
+    /// See [`Error::WriteHeadersAfterRecords`]
+    WriteHeadersAfterRecords,
+    /// See [`Error::Serialize`]
+    Serialize,
+    /// See [`Error::Unencodable`]
+    Unencodable,
+    /// See [`Error::QuoteRequired`]
+    QuoteRequired,
+    /// See [`Error::FormulaInjection`]
+    FormulaInjection,
+    /// See [`Error::RowLengthMismatch`]
+    RowLengthMismatch,
+    /// See [`Error::UnterminatedQuote`]
+    UnterminatedQuote,
+    /// See [`Error::GarbageAfterQuote`]
+    GarbageAfterQuote,
+    /// See [`Error::InvalidUtf8`]
+    InvalidUtf8,
+    /// See [`Error::FieldCountMismatch`]
+    FieldCountMismatch,
+    /// See [`Error::InvalidHeader`]
+    InvalidHeader,
+    /// See [`Error::Deserialize`]
+    Deserialize,
+    /// See [`Error::MissingField`]
+    MissingField,
+    /// See [`Error::Convert`]
+    Convert,
+    /// See [`Error::LimitExceeded`]
+    LimitExceeded,
+}
+
+impl ErrorKind {
+    /// Whether reading or writing may go on after the error of this kind.
+    /// For example a record with garbage after quote may be skipped but an unterminated quote
+    /// consumes the rest of the stream.
+    pub fn is_recoverable(self) -> bool {
+        match self {
+            Self::WriteHeadersAfterRecords
+            | Self::Serialize
+            | Self::Unencodable
+            | Self::QuoteRequired
+            | Self::FormulaInjection
+            | Self::RowLengthMismatch
+            | Self::GarbageAfterQuote
+            | Self::InvalidUtf8
+            | Self::FieldCountMismatch
+            | Self::InvalidHeader
+            | Self::Deserialize
+            | Self::MissingField
+            | Self::Convert
+            | Self::LimitExceeded => true,
+            Self::Custom | Self::Io | Self::StreamComplete | Self::UnterminatedQuote => false,
+        }
+    }
 }
 
 impl Error {
@@ -32,6 +199,73 @@ impl Error {
     pub fn custom(val: impl std::fmt::Display) -> Self {
         Self::Custom(val.to_string())
     }
+
+    /// Kind of the error
+    pub fn kind(&self) -> ErrorKind {
+        match self {
+            Self::Custom(_) => ErrorKind::Custom,
+            Self::Io(_) | Self::ReadFailed { .. } => ErrorKind::Io,
+            Self::StreamComplete => ErrorKind::StreamComplete,
+            Self::WriteHeadersAfterRecords => ErrorKind::WriteHeadersAfterRecords,
+            Self::Serialize(_) => ErrorKind::Serialize,
+            Self::Unencodable { .. } => ErrorKind::Unencodable,
+            Self::QuoteRequired { .. } => ErrorKind::QuoteRequired,
+            Self::FormulaInjection { .. } => ErrorKind::FormulaInjection,
+            Self::RowLengthMismatch { .. } => ErrorKind::RowLengthMismatch,
+            Self::UnterminatedQuote(_) => ErrorKind::UnterminatedQuote,
+            Self::GarbageAfterQuote(_) => ErrorKind::GarbageAfterQuote,
+            Self::InvalidUtf8(_) => ErrorKind::InvalidUtf8,
+            Self::FieldCountMismatch { .. } => ErrorKind::FieldCountMismatch,
+            Self::InvalidHeader { .. } => ErrorKind::InvalidHeader,
+            Self::Deserialize { .. } => ErrorKind::Deserialize,
+            Self::MissingField { .. } => ErrorKind::MissingField,
+            Self::Convert { .. } => ErrorKind::Convert,
+            Self::LimitExceeded { .. } => ErrorKind::LimitExceeded,
+        }
+    }
+
+    /// Whether reading or writing may go on after the error, see [`ErrorKind::is_recoverable`]
+    pub fn is_recoverable(&self) -> bool {
+        self.kind().is_recoverable()
+    }
+
+    /// Position in the CSV stream where error occurred if it is known
+    pub fn position(&self) -> Option<Position> {
+        match self {
+            Self::UnterminatedQuote(position)
+            | Self::GarbageAfterQuote(position)
+            | Self::InvalidUtf8(position)
+            | Self::FieldCountMismatch { position, .. }
+            | Self::InvalidHeader { position, .. }
+            | Self::Deserialize { position, .. }
+            | Self::LimitExceeded { position, .. }
+            | Self::ReadFailed { position, .. } => Some(*position),
+            Self::MissingField { position, .. } | Self::Convert { position, .. } => *position,
+            _ => None,
+        }
+    }
+
+    /// Underlying IO error if it is the one
+    pub fn io_error(&self) -> Option<&std::io::Error> {
+        match self {
+            Self::Io(source) | Self::ReadFailed { source, .. } => Some(source),
+            _ => None,
+        }
+    }
+
+    /// Expected and actual number of fields for the [`Error::FieldCountMismatch`]
+    /// and [`Error::RowLengthMismatch`]
+    pub fn field_count(&self) -> Option<(usize, usize)> {
+        match self {
+            Self::FieldCountMismatch {
+                expected, actual, ..
+            }
+            | Self::RowLengthMismatch {
+                expected, actual, ..
+            } => Some((*expected, *actual)),
+            _ => None,
+        }
+    }
 }
 
 impl From<&str> for Error {
@@ -42,8 +276,119 @@ impl From<&str> for Error {
 
 impl core::fmt::Display for Error {
     fn fmt(&self, fmt: &mut core::fmt::Formatter) -> core::result::Result<(), core::fmt::Error> {
-        write!(fmt, "{self:?}")
+        match self {
+            Self::Custom(description) => write!(fmt, "{description}"),
+            Self::Io(source) => write!(fmt, "IO error: {source}"),
+            Self::StreamComplete => write!(fmt, "CSV stream is complete"),
+            Self::WriteHeadersAfterRecords => {
+                write!(fmt, "headers must be written before any record")
+            }
+            Self::Serialize(message) => write!(fmt, "cannot serialize record: {message}"),
+            Self::Unencodable {
+                character,
+                encoding,
+            } => write!(
+                fmt,
+                "character {character:?} cannot be encoded in {encoding}"
+            ),
+            Self::QuoteRequired { column, field } => {
+                write!(
+                    fmt,
+                    "field {column} {field:?} cannot be written without quotes"
+                )
+            }
+            Self::FormulaInjection { column, field } => {
+                write!(fmt, "field {column} {field:?} may be executed as a formula")
+            }
+            Self::RowLengthMismatch {
+                row,
+                expected,
+                actual,
+            } => write!(
+                fmt,
+                "row {row} has {actual} fields but {expected} are expected"
+            ),
+            Self::UnterminatedQuote(position) => {
+                write!(fmt, "quoted field is not terminated in {position}")
+            }
+            Self::GarbageAfterQuote(position) => {
+                write!(fmt, "unexpected data after closing quote at {position}")
+            }
+            Self::InvalidUtf8(position) => write!(fmt, "invalid UTF-8 at {position}"),
+            Self::FieldCountMismatch {
+                position,
+                expected,
+                actual,
+            } => write!(
+                fmt,
+                "expected {expected} fields but found {actual} in {position}"
+            ),
+            Self::InvalidHeader {
+                position,
+                column,
+                name,
+            } => {
+                if name.is_empty() {
+                    write!(fmt, "header {column} is empty in {position}")
+                } else {
+                    write!(fmt, "header {column} {name:?} is duplicate in {position}")
+                }
+            }
+            Self::Deserialize {
+                position,
+                field,
+                header,
+                message,
+            } => {
+                write!(fmt, "cannot deserialize ")?;
+                match (field, header) {
+                    (Some(field), Some(header)) => write!(fmt, "field {field} ({header:?}) of ")?,
+                    (Some(field), None) => write!(fmt, "field {field} of ")?,
+                    _ => {}
+                }
+                write!(fmt, "{position}: {message}")
+            }
+            Self::MissingField { position, field } => {
+                write!(fmt, "no field {field} in ")?;
+                match position {
+                    Some(position) => write!(fmt, "{position}"),
+                    None => write!(fmt, "record"),
+                }
+            }
+            Self::Convert {
+                position,
+                column,
+                header,
+                text,
+                message,
+            } => {
+                write!(fmt, "cannot convert field {column} ")?;
+                if let Some(header) = header {
+                    write!(fmt, "({header:?}) ")?;
+                }
+                write!(fmt, "{text:?}")?;
+                if let Some(position) = position {
+                    write!(fmt, " of {position}")?;
+                }
+                write!(fmt, ": {message}")
+            }
+            Self::LimitExceeded {
+                position,
+                limit,
+                max,
+            } => write!(fmt, "record exceeds limit of {max} {limit} in {position}"),
+            Self::ReadFailed { position, source } => {
+                write!(fmt, "failed to read {position}: {source}")
+            }
+        }
     }
 }
 
-impl std::error::Error for Error {}
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Io(source) | Self::ReadFailed { source, .. } => Some(source),
+            _ => None,
+        }
+    }
+}
@@ -0,0 +1,73 @@
+use crate::HeaderNames;
+use std::collections::HashMap;
+
+/// CSV headers with an index to look up columns by name.
+/// [`CsvReader`](crate::CsvReader) shares it with every [`Record`](crate::Record) it reads.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Headers {
+    names: Box<[String]>,
+    index: HashMap<String, usize>,
+}
+
+impl Headers {
+    /// Build index for `names` with respect to the [policy](HeaderNames) for empty and duplicate names.
+    /// Fails with index of the first empty or duplicate name if the policy rejects it.
+    pub fn new(
+        names: impl IntoIterator<Item = impl Into<String>>,
+        policy: HeaderNames,
+    ) -> Result<Self, usize> {
+        let mut names = names.into_iter().map(Into::into).collect::<Vec<String>>();
+        let mut index = HashMap::with_capacity(names.len());
+        for (column, name) in names.iter_mut().enumerate() {
+            if name.is_empty() || index.contains_key(name) {
+                match policy {
+                    HeaderNames::Keep => {}
+                    HeaderNames::Reject => return Err(column),
+                    HeaderNames::Rename => *name = rename(name, column, &index),
+                    HeaderNames::RenameWith(rename) => *name = rename(name, column),
+                }
+            }
+            index.entry(name.clone()).or_insert(column);
+        }
+        Ok(Self {
+            names: names.into_boxed_slice(),
+            index,
+        })
+    }
+
+    /// Header names in the order of columns
+    pub fn names(&self) -> &[String] {
+        &self.names
+    }
+
+    /// Column index of the header. Finds the first column if names are duplicated.
+    pub fn index_of(&self, name: &str) -> Option<usize> {
+        self.index.get(name).copied()
+    }
+
+    /// Number of headers
+    pub fn len(&self) -> usize {
+        self.names.len()
+    }
+
+    /// There are no headers
+    pub fn is_empty(&self) -> bool {
+        self.names.is_empty()
+    }
+}
+
+/// Name empty header as `column_N` and duplicate header as `name_N` where N is the least free number
+fn rename(name: &str, column: usize, taken: &HashMap<String, usize>) -> String {
+    let (base, mut n) = if name.is_empty() {
+        ("column", column + 1)
+    } else {
+        (name, 2)
+    };
+    loop {
+        let renamed = format!("{base}_{n}");
+        if !taken.contains_key(&renamed) {
+            break renamed;
+        }
+        n += 1;
+    }
+}
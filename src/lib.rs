@@ -1,11 +1,37 @@
+#[cfg(feature = "tokio")]
+mod async_reader;
+#[cfg(feature = "tokio")]
+mod async_writer;
+mod de;
+mod encoding;
 mod error;
+mod headers;
 mod parse;
+mod position;
+mod push;
 mod reader;
+mod record;
+mod schema;
+mod ser;
 mod writer;
 
-pub use error::{Error, Result};
-pub use reader::{CsvReader, CsvReaderConfig};
-pub use writer::{CsvWriter, CsvWriterConfig, NewLine};
+#[cfg(feature = "tokio")]
+pub use async_reader::AsyncCsvReader;
+#[cfg(feature = "tokio")]
+pub use async_writer::AsyncCsvWriter;
+pub use encoding::SourceEncoding;
+pub use encoding_rs;
+pub use error::{Error, ErrorKind, Result};
+pub use headers::Headers;
+pub use position::Position;
+pub use push::CsvPushParser;
+pub use reader::{
+    CsvReader, CsvReaderConfig, DeserializeRecords, FieldCount, HeaderNames, Limit, Quarantine,
+    Records, Recovery, Trim, TypedRecords,
+};
+pub use record::{ByteFields, ByteRecord, Fields, Record, RecordIndex};
+pub use schema::{ColumnType, Converter, Schema, Value};
+pub use writer::{CsvWriter, CsvWriterConfig, NewLine, OnDrop, QuoteStyle, Sanitize, Terminator};
 
 #[cfg(test)]
 mod tests {
@@ -107,6 +133,227 @@ mod tests {
         )
     }
 
+    #[test]
+    fn error_position() {
+        let buf = "1,2,3\r\n4,\"5\"xyz,6\r\n\"7\",\"8\n\",9\r\n10,\"11".as_bytes();
+        let mut reader = reader::CsvReader::new(buf);
+        assert!(reader.next().unwrap().is_ok());
+        let err = reader.next().unwrap().unwrap_err();
+        assert_eq!(ErrorKind::GarbageAfterQuote, err.kind());
+        assert!(err.is_recoverable());
+        assert_eq!(
+            Some(Position {
+                record: 2,
+                line: 2,
+                column: 3,
+                byte: 9,
+            }),
+            err.position()
+        );
+        assert!(reader.next().unwrap().is_ok());
+        assert_eq!(
+            Position {
+                record: 4,
+                line: 5,
+                column: 1,
+                byte: 31,
+            },
+            reader.position()
+        );
+        let err = reader.next().unwrap().unwrap_err();
+        assert_eq!(ErrorKind::UnterminatedQuote, err.kind());
+        assert!(!err.is_recoverable());
+        assert_eq!(4, err.position().unwrap().record);
+        assert_eq!(5, err.position().unwrap().line);
+        assert!(reader.next().is_none());
+    }
+
+    #[test]
+    fn error_kinds() {
+        let buf = b"1,2,3\r\n4,\xff,6\r\n7,8,9\r\n\"10";
+        let mut reader = reader::CsvReader::new(&buf[..]);
+        assert!(reader.next().unwrap().is_ok());
+        let err = reader.next().unwrap().unwrap_err();
+        assert_eq!(ErrorKind::InvalidUtf8, err.kind());
+        assert!(err.is_recoverable());
+        assert_eq!(
+            "invalid UTF-8 at record 2, line 2, column 3, byte 9",
+            err.to_string()
+        );
+        assert_eq!(
+            vec!["7", "8", "9"],
+            reader.next().unwrap().unwrap().into_vec()
+        );
+        let err = reader.next().unwrap().unwrap_err();
+        assert_eq!(
+            "quoted field is not terminated in record 4, line 4, column 1, byte 21",
+            err.to_string()
+        );
+        assert!(err.io_error().is_none());
+        assert!(reader.next().is_none());
+    }
+
+    #[test]
+    fn read_into_record() {
+        let buf = "1,\"2\"\"\",3\r\n4,\"5\"xyz,6\r\n\"7\n\",8,9".as_bytes();
+        let mut reader = reader::CsvReader::new(buf);
+        let mut record = Record::new();
+        assert!(reader.read_record(&mut record).unwrap());
+        assert_eq!(Record::from_iter(["1", "2\"", "3"]), record);
+        assert!(reader.read_record(&mut record).is_err());
+        assert!(reader.read_record(&mut record).unwrap());
+        assert_eq!(3, record.len());
+        assert_eq!(vec!["7\n", "8", "9"], record.iter().collect::<Vec<_>>());
+        assert!(!reader.read_record(&mut record).unwrap());
+    }
+
+    #[test]
+    fn read_bytes() {
+        let buf = b"\xcc\xe0\xec\xe0,\"\xec\xfb\"\"\xeb\xe0\"\r\n\"\xf0\xe0\xec\xf3\"";
+        let mut reader = reader::CsvReader::new(&buf[..]);
+        let mut record = ByteRecord::new();
+        assert!(reader.read_byte_record(&mut record).unwrap());
+        assert_eq!(
+            ByteRecord::from_iter([&b"\xcc\xe0\xec\xe0"[..], b"\xec\xfb\"\xeb\xe0"]),
+            record
+        );
+        assert!(reader.read_byte_record(&mut record).unwrap());
+        assert_eq!(vec![b"\xf0\xe0\xec\xf3"], record.iter().collect::<Vec<_>>());
+        assert!(!reader.read_byte_record(&mut record).unwrap());
+    }
+
+    #[test]
+    fn read_lossy() {
+        let buf = b"1,\xff2\r\n\"\xc3\",4";
+        let mut reader =
+            reader::CsvReader::with_config(&buf[..], CsvReaderConfig::default().lossy_utf8(true));
+        assert_eq!(
+            vec!["1", "\u{FFFD}2"],
+            reader.next().unwrap().unwrap().into_vec()
+        );
+        assert_eq!(
+            vec!["\u{FFFD}", "4"],
+            reader.next().unwrap().unwrap().into_vec()
+        );
+        assert!(reader.next().is_none());
+    }
+
+    #[test]
+    fn read_legacy_encodings() {
+        let (buf, _, _) = encoding_rs::WINDOWS_1251.encode("мама,\"мыла\nраму\"\r\n");
+        let mut reader = reader::CsvReader::with_config(
+            buf.as_ref(),
+            CsvReaderConfig::default().encoding(SourceEncoding::Fixed(encoding_rs::WINDOWS_1251)),
+        );
+        assert_eq!(
+            vec!["мама", "мыла\nраму"],
+            reader.next().unwrap().unwrap().into_vec()
+        );
+        assert!(reader.next().is_none());
+
+        let (buf, _, _) = encoding_rs::KOI8_R.encode("мама,мыла");
+        let mut reader = reader::CsvReader::with_config(
+            buf.as_ref(),
+            CsvReaderConfig::default().encoding(SourceEncoding::Detect(encoding_rs::KOI8_R)),
+        );
+        assert_eq!(
+            vec!["мама", "мыла"],
+            reader.next().unwrap().unwrap().into_vec()
+        );
+    }
+
+    #[test]
+    fn detect_encoding_by_bom() {
+        let mut buf = vec![0xFF, 0xFE];
+        buf.extend(
+            "Col 1,Col 2\r\nмама,раму"
+                .encode_utf16()
+                .flat_map(u16::to_le_bytes),
+        );
+        let mut reader = reader::CsvReader::with_config(
+            buf.as_slice(),
+            CsvReaderConfig::default()
+                .has_headers(true)
+                .encoding(SourceEncoding::Detect(encoding_rs::WINDOWS_1251)),
+        );
+        assert_eq!(vec!["Col 1", "Col 2"], reader.headers().unwrap().to_vec());
+        assert_eq!(
+            vec!["мама", "раму"],
+            reader.next().unwrap().unwrap().into_vec()
+        );
+
+        let buf = "\u{FEFF}Col 1,Col 2\r\nмама,раму".as_bytes();
+        let mut reader = reader::CsvReader::with_config(
+            buf,
+            CsvReaderConfig::default()
+                .has_headers(true)
+                .encoding(SourceEncoding::Detect(encoding_rs::UTF_8)),
+        );
+        assert_eq!(vec!["Col 1", "Col 2"], reader.headers().unwrap().to_vec());
+        assert_eq!(
+            vec!["мама", "раму"],
+            reader.next().unwrap().unwrap().into_vec()
+        );
+    }
+
+    #[test]
+    fn strict_field_count() {
+        let buf = "a,b,c\r\n1,2\r\n3,4,5\r\n6,7,8,9".as_bytes();
+        let mut reader = reader::CsvReader::with_config(
+            buf,
+            CsvReaderConfig::default()
+                .has_headers(true)
+                .field_count(FieldCount::Strict),
+        );
+        let err = reader.next().unwrap().unwrap_err();
+        assert_eq!(Some((3, 2)), err.field_count());
+        assert_eq!(
+            "expected 3 fields but found 2 in record 2, line 2, column 1, byte 7",
+            err.to_string()
+        );
+        assert_eq!(
+            vec!["3", "4", "5"],
+            reader.next().unwrap().unwrap().into_vec()
+        );
+        assert_eq!(
+            Some((3, 4)),
+            reader.next().unwrap().unwrap_err().field_count()
+        );
+        assert!(reader.next().is_none());
+    }
+
+    #[test]
+    fn fit_field_count() {
+        let buf = "1,2,3\r\n4\r\n5,6,7,8".as_bytes();
+        let mut reader = reader::CsvReader::with_config(
+            buf,
+            CsvReaderConfig::default().field_count(FieldCount::Pad),
+        );
+        assert!(reader.next().unwrap().is_ok());
+        assert_eq!(
+            vec!["4", "", ""],
+            reader.next().unwrap().unwrap().into_vec()
+        );
+        assert_eq!(
+            ErrorKind::FieldCountMismatch,
+            reader.next().unwrap().unwrap_err().kind()
+        );
+
+        let mut reader = reader::CsvReader::with_config(
+            buf,
+            CsvReaderConfig::default().field_count(FieldCount::Truncate),
+        );
+        assert!(reader.next().unwrap().is_ok());
+        assert!(reader.next().unwrap().is_err());
+        assert_eq!(
+            vec!["5", "6", "7"],
+            reader.next().unwrap().unwrap().into_vec()
+        );
+
+        let reader = reader::CsvReader::new(buf);
+        assert_eq!(3, reader.filter_map(Result::ok).count());
+    }
+
     #[test]
     fn read_headers() {
         let buf = "Col 1,Col 2, \"Col 3\"\r\n1,2,3\r\n4,\"5\",6".as_bytes();
@@ -131,6 +378,561 @@ mod tests {
         );
     }
 
+    #[derive(Debug, PartialEq, serde::Deserialize, serde::Serialize)]
+    enum Unit {
+        Kg,
+        Pcs,
+    }
+
+    #[derive(Debug, PartialEq, serde::Deserialize, serde::Serialize)]
+    struct Goods {
+        name: String,
+        amount: f64,
+        unit: Unit,
+        discount: Option<u8>,
+    }
+
+    #[test]
+    fn deserialize_by_headers() {
+        let buf = "unit,name,discount,amount\r\nKg,мука,,1.5\r\nPcs,яйца,10,12".as_bytes();
+        let mut reader = CsvReader::with_config(buf, CsvReaderConfig::default().has_headers(true));
+        let goods = reader
+            .deserialize::<Goods>()
+            .collect::<Result<Vec<_>>>()
+            .unwrap();
+        assert_eq!(
+            vec![
+                Goods {
+                    name: "мука".to_owned(),
+                    amount: 1.5,
+                    unit: Unit::Kg,
+                    discount: None,
+                },
+                Goods {
+                    name: "яйца".to_owned(),
+                    amount: 12.,
+                    unit: Unit::Pcs,
+                    discount: Some(10),
+                },
+            ],
+            goods
+        );
+    }
+
+    #[test]
+    fn deserialize_by_position() {
+        let buf = "мука,1.5,Kg,\r\nяйца,12,Pcs,10".as_bytes();
+        let mut reader = CsvReader::new(buf);
+        let mut records = reader.deserialize::<Goods>();
+        assert_eq!(Unit::Kg, records.next().unwrap().unwrap().unit);
+        assert_eq!(Some(10), records.next().unwrap().unwrap().discount);
+        assert!(records.next().is_none());
+        let mut reader = CsvReader::new("1,a,2".as_bytes());
+        assert_eq!(
+            (1, "a".to_owned(), 2),
+            reader
+                .deserialize::<(i32, String, u64)>()
+                .next()
+                .unwrap()
+                .unwrap()
+        );
+    }
+
+    #[test]
+    fn deserialize_fail() {
+        let buf = "name,amount,unit,discount\r\nмука,1.5,Kg,\r\nяйца,a dozen,Pcs,".as_bytes();
+        let mut reader = CsvReader::with_config(buf, CsvReaderConfig::default().has_headers(true));
+        let mut records = reader.deserialize::<Goods>();
+        assert!(records.next().unwrap().is_ok());
+        let err = records.next().unwrap().unwrap_err();
+        assert_eq!(ErrorKind::Deserialize, err.kind());
+        let Error::Deserialize {
+            position,
+            field,
+            header,
+            ..
+        } = &err
+        else {
+            unreachable!()
+        };
+        assert_eq!(3, position.record);
+        assert_eq!(Some(1), *field);
+        assert_eq!(Some("amount"), header.as_deref());
+        assert!(err.to_string().starts_with(
+            "cannot deserialize field 1 (\"amount\") of record 3, line 3, column 1, byte 45: cannot parse \"a dozen\" as f64"
+        ));
+
+        let buf = "#x\n1,\"2\"3\n#y\n1,zz\n".as_bytes();
+        let config = CsvReaderConfig::default()
+            .comment("#")
+            .recovery(Recovery::Skip);
+        let mut reader = CsvReader::with_config(buf, config);
+        let err = reader
+            .deserialize::<(u8, u8)>()
+            .next()
+            .unwrap()
+            .unwrap_err();
+        let position = err.position().unwrap();
+        assert_eq!((2, 4), (position.record, position.line));
+    }
+
+    #[test]
+    fn records_by_headers() {
+        let buf = "name,amount\r\nмука,1.5\r\nяйца,12".as_bytes();
+        let mut reader =
+            reader::CsvReader::with_config(buf, CsvReaderConfig::default().has_headers(true));
+        let records = reader.records().collect::<Result<Vec<_>>>().unwrap();
+        assert!(std::ptr::eq(
+            reader.header_index().unwrap().as_ref(),
+            records[1].headers().unwrap()
+        ));
+        assert_eq!(Some("мука"), records[0].get("name"));
+        assert_eq!("12", &records[1]["amount"]);
+        assert_eq!("яйца", &records[1][0]);
+        assert_eq!(None, records[1].get("price"));
+        assert_eq!(
+            vec![("name", "яйца"), ("amount", "12")],
+            records[1].pairs().collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn invalid_header_names() {
+        let buf = "a,,a,b,a\r\n1,2,3,4,5".as_bytes();
+        let mut reader =
+            reader::CsvReader::with_config(buf, CsvReaderConfig::default().has_headers(true));
+        let record = reader.records().next().unwrap().unwrap();
+        assert_eq!("1", &record["a"]);
+
+        let mut reader = reader::CsvReader::with_config(
+            buf,
+            CsvReaderConfig::default()
+                .has_headers(true)
+                .header_names(HeaderNames::Rename),
+        );
+        assert_eq!(
+            vec!["a", "column_2", "a_2", "b", "a_3"],
+            reader.headers().unwrap().to_vec()
+        );
+        let record = reader.records().next().unwrap().unwrap();
+        assert_eq!("5", &record["a_3"]);
+
+        let reader = reader::CsvReader::with_config(
+            buf,
+            CsvReaderConfig::default()
+                .has_headers(true)
+                .header_names(HeaderNames::RenameWith(|name, column| {
+                    format!("{name}#{column}")
+                })),
+        );
+        assert_eq!(
+            vec!["a", "#1", "a#2", "b", "a#4"],
+            reader.headers().unwrap().to_vec()
+        );
+
+        let mut reader = reader::CsvReader::with_config(
+            buf,
+            CsvReaderConfig::default()
+                .has_headers(true)
+                .header_names(HeaderNames::Reject),
+        );
+        assert!(reader.headers().is_none());
+        let err = reader.next().unwrap().unwrap_err();
+        assert_eq!(ErrorKind::InvalidHeader, err.kind());
+        assert_eq!(
+            "header 1 is empty in record 1, line 1, column 1, byte 0",
+            err.to_string()
+        );
+        assert_eq!(
+            vec!["1", "2", "3", "4", "5"],
+            reader.next().unwrap().unwrap().into_vec()
+        );
+    }
+
+    #[test]
+    fn skip_preamble_and_comments() {
+        let buf = "Instrument export\r\n\r\n#device: X-100\r\nname,value\r\n#unit: mV\r\na,\"1\n#2\"\r\n#end\r\nb,3\r\n#".as_bytes();
+        let config = CsvReaderConfig::default()
+            .has_headers(true)
+            .skip_lines(2)
+            .comment('#');
+        let mut reader = CsvReader::with_config(buf, config);
+        assert_eq!(vec!["Instrument export", ""], reader.preamble());
+        assert_eq!(vec!["name", "value"], reader.headers().unwrap());
+        assert_eq!(vec!["device: X-100"], reader.comments());
+        assert_eq!(5, reader.position().line);
+        assert_eq!(
+            vec!["a", "1\n#2"],
+            reader.next().unwrap().unwrap().into_vec()
+        );
+        let record = reader.records().next().unwrap().unwrap();
+        assert_eq!("3", &record["value"]);
+        assert!(reader.next().is_none());
+        assert_eq!(
+            vec!["device: X-100", "unit: mV", "end", ""],
+            reader.comments()
+        );
+        assert_eq!(
+            Position {
+                record: 4,
+                line: 10,
+                column: 2,
+                byte: 82
+            },
+            reader.position()
+        );
+
+        let config = CsvReaderConfig::default().comment("");
+        assert_eq!(None, config.comment);
+        let config = CsvReaderConfig {
+            comment: Some(String::new()),
+            ..Default::default()
+        };
+        let mut reader = CsvReader::with_config("a,b\r\n#c".as_bytes(), config);
+        assert_eq!(2, reader.by_ref().count());
+        assert!(reader.comments().is_empty());
+    }
+
+    #[test]
+    fn trim_modes() {
+        let buf = " a , \"b\"\r\n 1 , \" 2 \"\r\n".as_bytes();
+        let read = |trim| {
+            let config = CsvReaderConfig::default().has_headers(true).trim(trim);
+            let mut reader = CsvReader::with_config(buf, config);
+            let record = reader.next().unwrap().map(<[String]>::into_vec);
+            (reader.headers().map(<[String]>::to_vec), record)
+        };
+        let (headers, record) = read(Trim::All);
+        assert_eq!(vec!["a", "b"], headers.unwrap());
+        assert_eq!(vec!["1", " 2 "], record.unwrap());
+        let (headers, record) = read(Trim::Headers);
+        assert_eq!(vec!["a", "b"], headers.unwrap());
+        assert_eq!(vec![" 1 ", " \" 2 \""], record.unwrap());
+        let (headers, record) = read(Trim::Fields);
+        assert_eq!(vec![" a ", " \"b\""], headers.unwrap());
+        assert_eq!(vec!["1", " 2 "], record.unwrap());
+        let (headers, record) = read(Trim::Quotes);
+        assert_eq!(vec![" a ", "b"], headers.unwrap());
+        assert_eq!(vec![" 1 ", " 2 "], record.unwrap());
+        let (headers, record) = read(Trim::None);
+        assert_eq!(vec![" a ", " \"b\""], headers.unwrap());
+        assert_eq!(vec![" 1 ", " \" 2 \""], record.unwrap());
+
+        let mut reader = CsvReader::new("4, 5 ,6".as_bytes());
+        assert_eq!(
+            vec!["4", " 5 ", "6"],
+            reader.next().unwrap().unwrap().into_vec()
+        );
+        let mut buf = Vec::new();
+        CsvWriter::new(&mut buf).write_row([" 5 "]).unwrap();
+        assert_eq!(b" 5 ", buf.as_slice());
+
+        let config = CsvReaderConfig::default().trim(Trim::None);
+        let mut reader = CsvReader::with_config("\"a\" ,b".as_bytes(), config);
+        let err = reader.next().unwrap().unwrap_err();
+        assert_eq!(ErrorKind::GarbageAfterQuote, err.kind());
+    }
+
+    #[test]
+    fn recovery_policies() {
+        use std::sync::{Arc, Mutex};
+
+        let buf = "a,b\r\n1,\"2\"x\r\n3,\"4\r\n5,6\r\n7,8".as_bytes();
+        let read = |recovery| {
+            let quarantined = Arc::new(Mutex::new(Vec::new()));
+            let sink = quarantined.clone();
+            let config = CsvReaderConfig::default()
+                .recovery(recovery)
+                .quarantine(move |raw, e| {
+                    let raw = String::from_utf8(raw.to_vec()).unwrap();
+                    sink.lock().unwrap().push((raw, e.kind()));
+                });
+            let records = CsvReader::with_config(buf, config)
+                .map(|row| row.map(<[String]>::into_vec).map_err(|e| e.kind()))
+                .collect::<Vec<_>>();
+            let quarantined = quarantined.lock().unwrap().clone();
+            (records, quarantined)
+        };
+
+        let (records, quarantined) = read(Recovery::Return);
+        assert_eq!(
+            vec![
+                Ok(vec!["a".to_owned(), "b".to_owned()]),
+                Err(ErrorKind::GarbageAfterQuote),
+                Err(ErrorKind::UnterminatedQuote)
+            ],
+            records
+        );
+        assert!(quarantined.is_empty());
+
+        let (records, _) = read(Recovery::Abort);
+        assert_eq!(
+            vec![
+                Ok(vec!["a".to_owned(), "b".to_owned()]),
+                Err(ErrorKind::GarbageAfterQuote)
+            ],
+            records
+        );
+
+        let (records, quarantined) = read(Recovery::Skip);
+        assert_eq!(vec![Ok(vec!["a".to_owned(), "b".to_owned()])], records);
+        assert_eq!(
+            vec![
+                ("1,\"2\"x\r\n".to_owned(), ErrorKind::GarbageAfterQuote),
+                (
+                    "3,\"4\r\n5,6\r\n7,8".to_owned(),
+                    ErrorKind::UnterminatedQuote
+                )
+            ],
+            quarantined
+        );
+
+        let (records, quarantined) = read(Recovery::Resync);
+        assert_eq!(
+            vec![
+                Ok(vec!["a".to_owned(), "b".to_owned()]),
+                Ok(vec!["5".to_owned(), "6".to_owned()]),
+                Ok(vec!["7".to_owned(), "8".to_owned()])
+            ],
+            records
+        );
+        assert_eq!(
+            vec![
+                ("1,\"2\"x\r\n".to_owned(), ErrorKind::GarbageAfterQuote),
+                ("3,\"4\r\n".to_owned(), ErrorKind::UnterminatedQuote)
+            ],
+            quarantined
+        );
+
+        let config = CsvReaderConfig::default().recovery(Recovery::Resync);
+        let mut reader = CsvReader::with_config(buf, config);
+        reader.nth(1).unwrap().unwrap();
+        assert_eq!(
+            Position {
+                record: 5,
+                line: 5,
+                column: 1,
+                byte: 24
+            },
+            reader.position()
+        );
+    }
+
+    #[test]
+    fn resync_complete_records() {
+        use std::sync::{Arc, Mutex};
+
+        // records failing the field checks are parsed completely, their lines are not read again
+        let read = |buf: &str, config: CsvReaderConfig| {
+            let quarantined = Arc::new(Mutex::new(Vec::new()));
+            let sink = quarantined.clone();
+            let config = config.recovery(Recovery::Resync).quarantine(move |raw, e| {
+                let raw = String::from_utf8(raw.to_vec()).unwrap();
+                sink.lock().unwrap().push((raw, e.kind()));
+            });
+            let records = CsvReader::with_config(buf.as_bytes(), config)
+                .map(|row| row.map(<[String]>::into_vec).map_err(|e| e.kind()))
+                .collect::<Vec<_>>();
+            let quarantined = quarantined.lock().unwrap().clone();
+            (records, quarantined)
+        };
+
+        let (records, quarantined) = read(
+            "a,b\n1,\"x\ny\",3\n4,5\n",
+            CsvReaderConfig::default().field_count(FieldCount::Strict),
+        );
+        assert_eq!(
+            vec![
+                Ok(vec!["a".to_owned(), "b".to_owned()]),
+                Ok(vec!["4".to_owned(), "5".to_owned()])
+            ],
+            records
+        );
+        assert_eq!(
+            vec![("1,\"x\ny\",3\n".to_owned(), ErrorKind::FieldCountMismatch)],
+            quarantined
+        );
+
+        let (records, quarantined) = read(
+            "1,\"long\nz,q\",2\n4,5\n",
+            CsvReaderConfig::default().max_field_bytes(2),
+        );
+        assert_eq!(vec![Ok(vec!["4".to_owned(), "5".to_owned()])], records);
+        assert_eq!(
+            vec![("1,\"long\nz,q\",2\n".to_owned(), ErrorKind::LimitExceeded)],
+            quarantined
+        );
+
+        let config = CsvReaderConfig::default()
+            .max_fields(2)
+            .recovery(Recovery::Resync);
+        let mut reader = CsvReader::with_config("1,\"2\n3\",4\n5,6\n".as_bytes(), config);
+        assert_eq!(vec!["5", "6"], reader.next().unwrap().unwrap().as_ref());
+        assert_eq!(4, reader.position().line);
+    }
+
+    #[test]
+    fn resource_limits() {
+        let read = |buf: &str, config: CsvReaderConfig| {
+            CsvReader::with_config(buf.as_bytes(), config)
+                .map(|row| row.map(<[String]>::into_vec))
+                .collect::<Vec<_>>()
+        };
+
+        // the rest of the record is skipped up to its end, the unterminated quote consumes the stream
+        let records = read(
+            "1,2\r\n\"3,4\r\n5,6\r\n7,8\r\n9,10",
+            CsvReaderConfig::default().max_lines(2),
+        );
+        assert_eq!(2, records.len());
+        assert_eq!(
+            ErrorKind::LimitExceeded,
+            records[1].as_ref().unwrap_err().kind()
+        );
+        let records = read(
+            "1,\"a\nb\nc\"\n4,5\n",
+            CsvReaderConfig::default().max_lines(2),
+        );
+        assert_eq!(2, records.len());
+        assert_eq!(vec!["4", "5"], *records[1].as_ref().unwrap());
+
+        let mut reader = CsvReader::with_config(
+            "1,\"ab\ncdefghijkl\"\n4,5\n".as_bytes(),
+            CsvReaderConfig::default().max_record_bytes(8),
+        );
+        assert!(reader.next().unwrap().is_err());
+        assert_eq!(3, reader.position().line);
+        assert_eq!(vec!["4", "5"], reader.next().unwrap().unwrap().into_vec());
+
+        // the resync drops the line cut by the limit, complete lines are read again
+        let config = || {
+            CsvReaderConfig::default()
+                .max_record_bytes(8)
+                .recovery(Recovery::Resync)
+        };
+        let mut reader = CsvReader::with_config("1,\"ab\ncdefghijkl\"\n4,5\n".as_bytes(), config());
+        let records = reader.records().collect::<Vec<_>>();
+        assert_eq!(1, records.len());
+        let record = records[0].as_ref().unwrap();
+        assert_eq!(vec!["4", "5"], record.iter().collect::<Vec<_>>());
+        assert_eq!(
+            Position {
+                record: 2,
+                line: 3,
+                column: 1,
+                byte: 18
+            },
+            record.position().unwrap()
+        );
+        let mut reader = CsvReader::with_config(
+            "1,\"ab\ncd\nefghijklmn\"\n4,5\n".as_bytes(),
+            config().max_record_bytes(10),
+        );
+        let records = reader.records().collect::<Result<Vec<_>>>().unwrap();
+        assert_eq!(
+            vec![vec!["cd"], vec!["4", "5"]],
+            records
+                .iter()
+                .map(|record| record.iter().collect::<Vec<_>>())
+                .collect::<Vec<_>>()
+        );
+        assert_eq!(
+            vec![(2, 2, 6), (3, 4, 21)],
+            records
+                .iter()
+                .map(|record| {
+                    let position = record.position().unwrap();
+                    (position.record, position.line, position.byte)
+                })
+                .collect::<Vec<_>>()
+        );
+
+        let mut reader = CsvReader::with_config(
+            "1,2\r\nabcdefghij,k\r\n3,4".as_bytes(),
+            CsvReaderConfig::default().max_record_bytes(8),
+        );
+        assert!(reader.next().unwrap().is_ok());
+        let err = reader.next().unwrap().unwrap_err();
+        assert_eq!(
+            "record exceeds limit of 8 bytes per record in record 2, line 2, column 1, byte 5",
+            err.to_string()
+        );
+        assert_eq!(
+            Position {
+                record: 3,
+                line: 3,
+                column: 1,
+                byte: 19
+            },
+            reader.position()
+        );
+        assert_eq!(vec!["3", "4"], reader.next().unwrap().unwrap().into_vec());
+
+        let records = read("1,2,3\r\n4,5", CsvReaderConfig::default().max_fields(2));
+        assert!(matches!(
+            records[0],
+            Err(Error::LimitExceeded {
+                limit: Limit::Fields,
+                max: 2,
+                ..
+            })
+        ));
+        assert_eq!(vec!["4", "5"], *records[1].as_ref().unwrap());
+
+        let records = read(
+            "1234,5\r\n6,7",
+            CsvReaderConfig::default().max_field_bytes(3),
+        );
+        assert!(matches!(
+            records[0],
+            Err(Error::LimitExceeded {
+                limit: Limit::FieldBytes,
+                ..
+            })
+        ));
+        assert_eq!(vec!["6", "7"], *records[1].as_ref().unwrap());
+    }
+
+    #[test]
+    fn skip_oversized_records() {
+        // the first line is longer than pieces the rest of the record is scanned by
+        let long = "a\"\"b||".repeat(3000) + &"\na\"\"b||".repeat(10);
+        let src = format!("x||\"{long}\"||y\r\n4||5\r\n6||\"7\r\n8\"\r\n9||10");
+        let config = || {
+            CsvReaderConfig::default()
+                .separator("||")
+                .max_record_bytes(16)
+                .max_lines(3)
+        };
+        let expected = vec![
+            None,
+            Some(vec!["4".to_string(), "5".to_string()]),
+            Some(vec!["6".to_string(), "7\r\n8".to_string()]),
+            Some(vec!["9".to_string(), "10".to_string()]),
+        ];
+        let records = CsvReader::with_config(src.as_bytes(), config())
+            .map(|row| row.ok().map(<[String]>::into_vec))
+            .collect::<Vec<_>>();
+        assert_eq!(expected, records);
+
+        // the rest of a record is skipped as the chunks arrive
+        let mut parser = CsvPushParser::with_config(config());
+        let mut records = Vec::new();
+        for b in src.as_bytes() {
+            parser.feed(&[*b]);
+            records.extend(parser.by_ref().map(|record| {
+                record
+                    .ok()
+                    .map(|record| record.iter().map(str::to_owned).collect::<Vec<_>>())
+            }));
+        }
+        parser.finish();
+        records.extend(
+            parser.map(|record| record.ok().map(|record| record.to_boxed_slice().into_vec())),
+        );
+        assert_eq!(expected, records);
+    }
+
     #[test]
     fn writer_works() {
         let mut buf = Vec::new();
@@ -138,9 +940,684 @@ mod tests {
         let mut writer = CsvWriter::new(&mut buf);
         writer.write_row(&row).unwrap();
         writer.write_row(["4", "5\"abc\"X", "6\n\tagain"]).unwrap();
+        drop(writer);
         assert_eq!(
             b"1,2,3\r\n4,\"5\"\"abc\"\"X\",\"6\n\tagain\"",
             buf.as_slice()
         );
     }
+
+    #[test]
+    fn writer_lifecycle() {
+        struct Broken;
+
+        impl std::io::Write for Broken {
+            fn write(&mut self, _buf: &[u8]) -> std::io::Result<usize> {
+                Err(std::io::ErrorKind::BrokenPipe.into())
+            }
+
+            fn flush(&mut self) -> std::io::Result<()> {
+                Ok(())
+            }
+        }
+
+        let mut writer = CsvWriter::new(Vec::new());
+        writer.write_row(["1", "2"]).unwrap();
+        assert!(writer.get_ref().is_empty());
+        writer.flush().unwrap();
+        assert_eq!(b"1,2", writer.get_ref().as_slice());
+        writer.write_row(["3", "4"]).unwrap();
+        assert_eq!(b"1,2\r\n3,4", writer.into_inner().unwrap().as_slice());
+
+        let mut writer =
+            CsvWriter::with_config(Vec::new(), CsvWriterConfig::default().buffer_capacity(4));
+        writer.write_row(["1"]).unwrap();
+        assert!(writer.get_ref().is_empty());
+        writer.write_row(["2"]).unwrap();
+        assert_eq!(b"1\r\n2", writer.get_ref().as_slice());
+
+        let mut writer = CsvWriter::new(Broken);
+        writer.write_row(["1", "2"]).unwrap();
+        assert_eq!(
+            Some(std::io::ErrorKind::BrokenPipe),
+            writer.flush().unwrap_err().io_error().map(|e| e.kind())
+        );
+        assert!(
+            CsvWriter::with_config(Broken, CsvWriterConfig::default().buffer_capacity(0))
+                .write_row(["1"])
+                .is_err()
+        );
+
+        let (sender, receiver) = std::sync::mpsc::channel();
+        let config =
+            CsvWriterConfig::default().report_on_drop(move |e| sender.send(e.kind()).unwrap());
+        let mut writer = CsvWriter::with_config(Broken, config);
+        writer.write_row(["1", "2"]).unwrap();
+        drop(writer);
+        assert_eq!(Ok(ErrorKind::Io), receiver.try_recv());
+
+        let dropped = std::panic::catch_unwind(|| {
+            let mut writer = CsvWriter::new(Broken);
+            writer.write_row(["1", "2"]).unwrap();
+        });
+        assert!(dropped.is_ok());
+        let dropped = std::panic::catch_unwind(|| {
+            let config = CsvWriterConfig::default().on_drop(OnDrop::Panic);
+            let mut writer = CsvWriter::with_config(Broken, config);
+            writer.write_row(["1", "2"]).unwrap();
+        });
+        assert!(dropped.is_err());
+        let mut writer =
+            CsvWriter::with_config(Broken, CsvWriterConfig::default().on_drop(OnDrop::Ignore));
+        writer.write_row(["1", "2"]).unwrap();
+    }
+
+    #[test]
+    fn writer_terminator() {
+        let write = |config: CsvWriterConfig| {
+            let mut writer = CsvWriter::with_config(Vec::new(), config);
+            writer.write_headers(&["a", "b"]).unwrap();
+            writer.write_row(["1", "2"]).unwrap();
+            writer.write_document(&[["3", "4"], ["5", "6"]]).unwrap();
+            String::from_utf8(writer.into_inner().unwrap()).unwrap()
+        };
+        assert_eq!(
+            "a,b\r\n1,2\r\n3,4\r\n5,6",
+            write(CsvWriterConfig::default())
+        );
+        assert_eq!(
+            "a,b\n1,2\n3,4\n5,6\n",
+            write(
+                CsvWriterConfig::default()
+                    .unix_end_of_line()
+                    .terminator(Terminator::AfterEach)
+            )
+        );
+        let mut first = CsvWriter::with_config(
+            Vec::new(),
+            CsvWriterConfig::default().terminator(Terminator::AfterEach),
+        );
+        first.write_row(["1", "\"2\n3\""]).unwrap();
+        let mut concatenated = first.into_inner().unwrap();
+        concatenated
+            .extend(write(CsvWriterConfig::default().terminator(Terminator::AfterEach)).bytes());
+        let records = CsvReader::new(concatenated.as_slice())
+            .map(|record| record.unwrap().into_vec())
+            .collect::<Vec<_>>();
+        assert_eq!(5, records.len());
+        assert_eq!(vec!["1", "\"2\n3\""], records[0]);
+    }
+
+    #[test]
+    fn write_display_fields() {
+        let config = || {
+            CsvWriterConfig::default()
+                .field_count(FieldCount::Truncate)
+                .quote_style(QuoteStyle::NonNumeric)
+        };
+        let mut expected = CsvWriter::with_config(Vec::new(), config());
+        expected.write_row(["x", "y"]).unwrap();
+        expected.write_row(["1", "0.5"]).unwrap();
+        expected.write_row(["2", "a;b"]).unwrap();
+        let mut writer = CsvWriter::with_config(Vec::new(), config());
+        writer.write_fields(["x", "y"]).unwrap();
+        writer.write_fields([1.0, 0.5, 7.0]).unwrap();
+        writer.write_field(2).unwrap();
+        writer.write_field("a;b").unwrap();
+        writer
+            .write_field(std::path::Path::new("z").display())
+            .unwrap();
+        writer.end_record().unwrap();
+        let written = writer.into_inner().unwrap();
+        assert_eq!(expected.into_inner().unwrap(), written);
+        assert_eq!(
+            "\"x\",\"y\"\r\n1,0.5\r\n2,\"a;b\"",
+            String::from_utf8(written).unwrap()
+        );
+
+        let mut writer = CsvWriter::with_config(
+            Vec::new(),
+            CsvWriterConfig::default().quote_style(QuoteStyle::Never),
+        );
+        writer.write_field(1).unwrap();
+        assert_eq!(
+            ErrorKind::QuoteRequired,
+            writer.write_field("a,b").unwrap_err().kind()
+        );
+        writer.write_field(2).unwrap();
+        assert_eq!(ErrorKind::Custom, writer.end_record().unwrap_err().kind());
+        writer.write_field(3).unwrap();
+        writer.end_record().unwrap();
+        assert_eq!(
+            ErrorKind::QuoteRequired,
+            writer.write_row(["4", "a,b"]).unwrap_err().kind()
+        );
+        writer.write_row(["5"]).unwrap();
+        assert_eq!(b"3\r\n5", writer.into_inner().unwrap().as_slice());
+
+        let mut writer = CsvWriter::with_config(
+            Vec::new(),
+            CsvWriterConfig::default().sanitize(Sanitize::Reject),
+        );
+        writer.write_field("x").unwrap();
+        assert_eq!(
+            ErrorKind::FormulaInjection,
+            writer.write_field("=1").unwrap_err().kind()
+        );
+        writer.write_field("z").unwrap();
+        assert!(writer.end_record().is_err());
+        writer.write_fields(["a", "b"]).unwrap();
+        assert_eq!(b"a,b", writer.into_inner().unwrap().as_slice());
+    }
+
+    #[test]
+    fn writer_field_count() {
+        let write = |config: CsvWriterConfig| {
+            let mut buf = Vec::new();
+            let mut writer = CsvWriter::with_config(&mut buf, config);
+            writer.write_headers(&["name", "amount", "unit"]).unwrap();
+            let results = [
+                writer.write_row(["milk", "1.5", "l"]),
+                writer.write_row(["bread", "1"]),
+                writer.write_row(["tea", "2", "box", "black"]),
+            ];
+            drop(writer);
+            (
+                results.map(|result| result.map_err(|e| e.to_string())),
+                String::from_utf8(buf).unwrap(),
+            )
+        };
+        let (results, written) = write(CsvWriterConfig::default());
+        assert!(results.iter().all(|result| result.is_ok()));
+        assert_eq!(
+            "name,amount,unit\r\nmilk,1.5,l\r\nbread,1\r\ntea,2,box,black",
+            written
+        );
+
+        let (results, written) = write(CsvWriterConfig::default().field_count(FieldCount::Strict));
+        assert_eq!(
+            Err("row 2 has 2 fields but 3 are expected".to_string()),
+            results[1]
+        );
+        assert_eq!(
+            Err("row 2 has 4 fields but 3 are expected".to_string()),
+            results[2]
+        );
+        assert_eq!("name,amount,unit\r\nmilk,1.5,l", written);
+
+        let (results, written) = write(
+            CsvWriterConfig::default()
+                .field_count(FieldCount::Pad)
+                .quote_style(QuoteStyle::Always),
+        );
+        assert!(results[1].is_ok());
+        assert!(results[2].is_err());
+        assert_eq!(
+            "\"name\",\"amount\",\"unit\"\r\n\"milk\",\"1.5\",\"l\"\r\n\"bread\",\"1\",\"\"",
+            written
+        );
+
+        let mut buf = Vec::new();
+        let mut writer = CsvWriter::with_config(
+            &mut buf,
+            CsvWriterConfig::default().field_count(FieldCount::Strict),
+        );
+        writer.write_row(["1", "2"]).unwrap();
+        let err = writer.write_row(["3"]).unwrap_err();
+        assert!(matches!(
+            err,
+            Error::RowLengthMismatch {
+                row: 1,
+                expected: 2,
+                actual: 1
+            }
+        ));
+        assert_eq!(Some((2, 1)), err.field_count());
+    }
+
+    #[test]
+    fn sanitize_formulas() {
+        let row = ["=SUM(A1)", "-1.5", "+7", "@cmd", "\tx", "-", "a=b"];
+        let write = |config: CsvWriterConfig| {
+            let mut writer = CsvWriter::with_config(Vec::new(), config);
+            writer
+                .write_row(row)
+                .map(|_| String::from_utf8(writer.into_inner().unwrap()).unwrap())
+        };
+        assert_eq!(
+            "=SUM(A1),-1.5,+7,@cmd,\"\tx\",-,a=b",
+            write(CsvWriterConfig::default()).unwrap()
+        );
+        assert_eq!(
+            "'=SUM(A1),'-1.5,'+7,'@cmd,\"'\tx\",'-,a=b",
+            write(CsvWriterConfig::default().sanitize(Sanitize::Prefix)).unwrap()
+        );
+        assert_eq!(
+            "'=SUM(A1),-1.5,+7,'@cmd,\"'\tx\",'-,a=b",
+            write(
+                CsvWriterConfig::default()
+                    .sanitize(Sanitize::Prefix)
+                    .allow_numbers(1)
+                    .allow_numbers(2)
+                    .allow_numbers(5)
+            )
+            .unwrap()
+        );
+        let err = write(
+            CsvWriterConfig::default()
+                .sanitize(Sanitize::Reject)
+                .allow_numbers(0),
+        )
+        .unwrap_err();
+        assert_eq!(ErrorKind::FormulaInjection, err.kind());
+        assert_eq!(
+            "field 0 \"=SUM(A1)\" may be executed as a formula",
+            err.to_string()
+        );
+    }
+
+    #[test]
+    fn quote_styles() {
+        let row = ["milk", "1.5", "", "-2e3", "a,b"];
+        let write = |config: CsvWriterConfig| {
+            let mut writer = CsvWriter::with_config(Vec::new(), config);
+            writer
+                .write_row(row)
+                .map(|_| String::from_utf8(writer.into_inner().unwrap()).unwrap())
+        };
+        assert_eq!(
+            "\"milk\",\"1.5\",\"\",\"-2e3\",\"a,b\"",
+            write(CsvWriterConfig::default().quote_style(QuoteStyle::Always)).unwrap()
+        );
+        assert_eq!(
+            "\"milk\",1.5,\"\",-2e3,\"a,b\"",
+            write(CsvWriterConfig::default().quote_style(QuoteStyle::NonNumeric)).unwrap()
+        );
+        assert_eq!(
+            "\"milk\",1.5,,-2e3,\"a,b\"",
+            write(CsvWriterConfig::default().column_quote_style(0, QuoteStyle::Always)).unwrap()
+        );
+        let err = write(CsvWriterConfig::default().quote_style(QuoteStyle::Never)).unwrap_err();
+        assert_eq!(ErrorKind::QuoteRequired, err.kind());
+        assert_eq!(
+            "field 4 \"a,b\" cannot be written without quotes",
+            err.to_string()
+        );
+        assert_eq!(
+            "milk;1.5;;-2e3;a,b",
+            write(
+                CsvWriterConfig::default()
+                    .separator(';')
+                    .quote_style(QuoteStyle::Never)
+            )
+            .unwrap()
+        );
+    }
+
+    #[test]
+    fn quote_and_escape() {
+        let row = ["C:\\temp", "say \"hi\"", "it's", "a;b"];
+        for (quote, escape, written) in [
+            ('"', '"', "C:\\temp;\"say \"\"hi\"\"\";it's;\"a;b\""),
+            ('"', '\\', "\"C:\\\\temp\";\"say \\\"hi\\\"\";it's;\"a;b\""),
+            ('\'', '\\', "'C:\\\\temp';say \"hi\";'it\\'s';'a;b'"),
+        ] {
+            let mut buf = Vec::new();
+            let config = CsvWriterConfig::default()
+                .separator(';')
+                .quote(quote)
+                .escape(escape);
+            CsvWriter::with_config(&mut buf, config)
+                .write_row(row)
+                .unwrap();
+            assert_eq!(written, String::from_utf8(buf.clone()).unwrap());
+
+            let config = CsvReaderConfig::default()
+                .separator(';')
+                .quote(quote)
+                .escape(escape);
+            let mut reader = CsvReader::with_config(buf.as_slice(), config);
+            assert_eq!(row.to_vec(), reader.next().unwrap().unwrap().into_vec());
+        }
+
+        // the escape works inside quoted fields only and escaped characters are kept as is
+        let config = CsvReaderConfig::default().escape('\\');
+        let buf = "C:\\temp,\"say \\\"hi\\\"\",\"a\\\\b\\t\"".as_bytes();
+        let mut reader = CsvReader::with_config(buf, config);
+        assert_eq!(
+            vec!["C:\\temp", "say \"hi\"", "a\\bt"],
+            reader.next().unwrap().unwrap().into_vec()
+        );
+    }
+
+    #[test]
+    fn string_separators_round_trip() {
+        let doc = [
+            vec!["a|b", "||", "x;", ";y", " pad ", ""],
+            vec!["<-", "->", "line\nbreak", " \"q\" ", "→", "\t"],
+        ];
+        for separator in [",", "||", ";;", "\t", "<->", "→"] {
+            let mut buf = Vec::new();
+            let config = CsvWriterConfig::default().separator(separator);
+            CsvWriter::with_config(&mut buf, config)
+                .write_document(&doc)
+                .unwrap();
+            let config = CsvReaderConfig::default().separator(separator);
+            let read = CsvReader::with_config(buf.as_slice(), config)
+                .map(|row| row.unwrap().into_vec())
+                .collect::<Vec<_>>();
+            assert_eq!(doc.to_vec(), read, "separator {separator:?}");
+        }
+    }
+
+    #[test]
+    fn serialize_with_headers() {
+        let mut buf = Vec::new();
+        let mut writer = CsvWriter::new(&mut buf);
+        writer
+            .serialize(&Goods {
+                name: "мука, высший сорт".to_owned(),
+                amount: 1.5,
+                unit: Unit::Kg,
+                discount: None,
+            })
+            .unwrap();
+        writer
+            .serialize(&Goods {
+                name: "яйца".to_owned(),
+                amount: 12.,
+                unit: Unit::Pcs,
+                discount: Some(10),
+            })
+            .unwrap();
+        drop(writer);
+        assert_eq!(
+            "name,amount,unit,discount\r\n\"мука, высший сорт\",1.5,Kg,\r\nяйца,12,Pcs,10",
+            String::from_utf8(buf).unwrap()
+        );
+    }
+
+    #[test]
+    fn serialize_after_headers() {
+        #[derive(serde::Serialize)]
+        enum Amount {
+            Exact(u32),
+            Unknown,
+        }
+
+        let mut buf = Vec::new();
+        let mut writer = CsvWriter::new(&mut buf);
+        writer.write_headers(&["x", "y", "z"]).unwrap();
+        writer
+            .serialize(&(1, Some(Some(-2.5)), Amount::Exact(3)))
+            .unwrap();
+        writer.serialize(&[Amount::Unknown]).unwrap();
+        let err = writer.serialize(&(1, (2, 3))).unwrap_err();
+        assert_eq!(ErrorKind::Serialize, err.kind());
+        assert!(matches!(
+            writer.write_headers(&["a"]),
+            Err(Error::WriteHeadersAfterRecords)
+        ));
+        drop(writer);
+        assert_eq!(
+            "x,y,z\r\n1,-2.5,3\r\nUnknown",
+            String::from_utf8(buf).unwrap()
+        );
+    }
+
+    #[test]
+    fn write_legacy_encodings() {
+        let mut buf = Vec::new();
+        let mut writer = CsvWriter::with_config(
+            &mut buf,
+            CsvWriterConfig::default().encoding(encoding_rs::WINDOWS_1251),
+        );
+        writer.write_row(["мама", "мыла, раму"]).unwrap();
+        let err = writer.write_row(["€", "日本"]).unwrap_err();
+        assert_eq!(ErrorKind::Unencodable, err.kind());
+        drop(writer);
+        assert_eq!(
+            encoding_rs::WINDOWS_1251.encode("мама,\"мыла, раму\"").0,
+            buf.as_slice()
+        );
+
+        let mut buf = Vec::new();
+        let mut writer = CsvWriter::with_config(
+            &mut buf,
+            CsvWriterConfig::default()
+                .encoding(encoding_rs::UTF_16LE)
+                .bom(true),
+        );
+        writer.write_row(["мама", "раму"]).unwrap();
+        writer.write_row(["1", "2"]).unwrap();
+        drop(writer);
+        let mut expected = vec![0xFF, 0xFE];
+        expected.extend("мама,раму\r\n1,2".encode_utf16().flat_map(u16::to_le_bytes));
+        assert_eq!(expected, buf);
+    }
+
+    #[test]
+    fn push_parser_chunks() {
+        fn config() -> CsvReaderConfig {
+            CsvReaderConfig::default()
+                .has_headers(true)
+                .skip_lines(1)
+                .comment("#")
+                .quote('«')
+                .escape('\\')
+                .separator("||")
+        }
+        let src = "preamble\n# comment\nимя||сумма\r\n«молоко\n\\«2\\««||1.5\r\n# и ещё\r\nхлеб|| «3« \r\nчай||«";
+        let expected = CsvReader::with_config(src.as_bytes(), config())
+            .records()
+            .map(|record| record.map_err(|e| e.kind()))
+            .collect::<Vec<_>>();
+        assert_eq!(3, expected.len());
+        assert_eq!("молоко\n«2«", &expected[0].as_ref().unwrap()["имя"]);
+        assert_eq!("3", &expected[1].as_ref().unwrap()["сумма"]);
+        assert_eq!(Err(ErrorKind::UnterminatedQuote), expected[2]);
+        for size in 1..src.len() {
+            let mut parser = CsvPushParser::with_config(config());
+            let mut records = Vec::new();
+            for chunk in src.as_bytes().chunks(size) {
+                parser.feed(chunk);
+                records.extend(parser.by_ref().map(|record| record.map_err(|e| e.kind())));
+            }
+            assert_eq!(2, records.len(), "chunk size {size}");
+            parser.finish();
+            records.extend(parser.by_ref().map(|record| record.map_err(|e| e.kind())));
+            assert_eq!(expected, records, "chunk size {size}");
+            assert_eq!(
+                Some(&["имя".to_string(), "сумма".to_string()][..]),
+                parser.headers()
+            );
+            assert_eq!(["preamble"], parser.preamble());
+            assert_eq!([" comment", " и ещё"], parser.comments());
+        }
+    }
+
+    #[test]
+    fn push_parser_decodes() {
+        let mut buf = vec![0xFF, 0xFE];
+        buf.extend(
+            "мама,\"мыла\nраму\"\r\n1,2"
+                .encode_utf16()
+                .flat_map(u16::to_le_bytes),
+        );
+        let mut parser = CsvPushParser::with_config(
+            CsvReaderConfig::default().encoding(SourceEncoding::Detect(encoding_rs::UTF_8)),
+        );
+        let mut record = ByteRecord::new();
+        let mut records = Vec::new();
+        for byte in &buf {
+            parser.feed(std::slice::from_ref(byte));
+            while parser.read_byte_record(&mut record).unwrap() {
+                records.push(record.clone());
+            }
+        }
+        assert_eq!(1, records.len());
+        parser.finish();
+        assert!(parser.read_byte_record(&mut record).unwrap());
+        records.push(record.clone());
+        assert!(!parser.read_byte_record(&mut record).unwrap());
+        assert_eq!(
+            vec![
+                ByteRecord::from_iter(["мама", "мыла\nраму"]),
+                ByteRecord::from_iter(["1", "2"])
+            ],
+            records
+        );
+    }
+
+    #[test]
+    fn parse_typed_fields() {
+        let buf = "name,amount\r\nmilk,1.5\r\nsugar,много".as_bytes();
+        let config = CsvReaderConfig::default().has_headers(true);
+        let mut reader = CsvReader::with_config(buf, config);
+        let records = reader.records().collect::<Result<Vec<_>>>().unwrap();
+        assert_eq!(1.5, records[0].parse::<f64>("amount").unwrap());
+        assert_eq!("milk", records[0].parse::<String>(0).unwrap());
+        let err = records[1].parse::<f64>("amount").unwrap_err();
+        assert_eq!(ErrorKind::Convert, err.kind());
+        assert_eq!(3, err.position().unwrap().record);
+        let Error::Convert {
+            column,
+            header,
+            text,
+            ..
+        } = &err
+        else {
+            panic!("unexpected error {err}");
+        };
+        assert_eq!(
+            (1, Some("amount"), "много"),
+            (*column, header.as_deref(), text.as_str())
+        );
+        let err = records[1].parse::<f64>("price").unwrap_err();
+        assert!(matches!(err, Error::MissingField { ref field, .. } if field == "price"));
+        let err = Record::from_iter(["x"]).parse::<i64>(0).unwrap_err();
+        assert_eq!(None, err.position());
+        assert!(err.is_recoverable());
+    }
+
+    #[test]
+    fn typed_records_by_schema() {
+        let schema = Schema::new()
+            .column("id", ColumnType::Int)
+            .optional("price", ColumnType::Float)
+            .column("sale", ColumnType::Bool)
+            .custom("price_cents", |text| {
+                let (units, cents) = text.split_once('.').ok_or("no cents")?;
+                let units = units.parse::<i64>().map_err(|e| e.to_string())?;
+                let cents = cents.parse::<i64>().map_err(|e| e.to_string())?;
+                Ok(Value::Int(units * 100 + cents))
+            });
+        let buf = "1,2.5,true,2.50\n2,,false,3.05\n3,x,true,1.00\n4,1,false,7".as_bytes();
+        let mut reader = CsvReader::new(buf);
+        let rows = reader.typed(&schema).collect::<Vec<_>>();
+        assert_eq!(4, rows.len());
+        assert_eq!(
+            &vec![
+                Value::Int(1),
+                Value::Float(2.5),
+                Value::Bool(true),
+                Value::Int(250)
+            ],
+            rows[0].as_ref().unwrap()
+        );
+        assert_eq!(Value::Null, rows[1].as_ref().unwrap()[1]);
+        assert_eq!(Value::Int(305), rows[1].as_ref().unwrap()[3]);
+        let err = rows[2].as_ref().unwrap_err();
+        assert_eq!(3, err.position().unwrap().record);
+        assert!(matches!(
+            err,
+            Error::Convert { column: 1, header: Some(header), text, .. }
+                if header == "price" && text == "x"
+        ));
+        assert_eq!(
+            "cannot convert field 3 (\"price_cents\") \"7\" of record 4, line 4, column 1, byte 44: no cents",
+            rows[3].as_ref().unwrap_err().to_string()
+        );
+
+        let buf = "sale,id\ntrue,1".as_bytes();
+        let mut reader = CsvReader::with_config(buf, CsvReaderConfig::default().has_headers(true));
+        let err = reader.typed(&schema).next().unwrap().unwrap_err();
+        assert!(matches!(err, Error::MissingField { ref field, .. } if field == "price"));
+    }
+
+    #[cfg(feature = "tokio")]
+    #[tokio::test]
+    async fn async_reader_stream() {
+        use futures_core::Stream;
+
+        let src = "# прайс\nимя,сумма\r\n\"молоко\n2\",1.5\r\nхлеб, 3 \r\n\"чай";
+        // tiny buffer splits records, UTF-8 sequences and quotes between chunks
+        let source = tokio::io::BufReader::with_capacity(3, src.as_bytes());
+        let config = CsvReaderConfig::default()
+            .has_headers(true)
+            .comment("#")
+            .trim(Trim::All);
+        let mut reader = AsyncCsvReader::with_config(source, config);
+        let mut records = Vec::new();
+        while let Some(record) =
+            std::future::poll_fn(|cx| std::pin::Pin::new(&mut reader).poll_next(cx)).await
+        {
+            records.push(record);
+        }
+        assert_eq!(
+            Some(&["имя".to_string(), "сумма".to_string()][..]),
+            reader.headers()
+        );
+        assert_eq!([" прайс"], reader.comments());
+        assert_eq!(3, records.len());
+        assert_eq!("молоко\n2", &records[0].as_ref().unwrap()["имя"]);
+        assert_eq!(
+            vec![("имя", "хлеб"), ("сумма", "3")],
+            records[1].as_ref().unwrap().pairs().collect::<Vec<_>>()
+        );
+        assert!(matches!(records[2], Err(Error::UnterminatedQuote(_))));
+    }
+
+    #[cfg(feature = "tokio")]
+    #[tokio::test]
+    async fn async_writer_same_as_sync() {
+        fn config() -> CsvWriterConfig {
+            CsvWriterConfig::default()
+                .separator(";")
+                .quote_style(QuoteStyle::NonNumeric)
+                .encoding(encoding_rs::WINDOWS_1251)
+        }
+        let doc = [["имя", "сумма"], ["молоко; 2", "1.5"], [" \"чай\"", "3"]];
+        let mut expected = Vec::new();
+        CsvWriter::with_config(&mut expected, config())
+            .write_document(&doc)
+            .unwrap();
+        let mut writer = AsyncCsvWriter::with_config(Vec::new(), config());
+        writer.write_headers(&doc[0]).await.unwrap();
+        writer.write_document(&doc[1..]).await.unwrap();
+        assert_eq!(expected, writer.into_inner().await.unwrap());
+    }
+
+    #[cfg(feature = "tokio")]
+    #[tokio::test]
+    async fn async_writer_drop() {
+        use std::sync::{Arc, Mutex};
+
+        let reported = Arc::new(Mutex::new(Vec::new()));
+        let sink = reported.clone();
+        let config =
+            CsvWriterConfig::default().report_on_drop(move |e| sink.lock().unwrap().push(e.kind()));
+        let mut writer = AsyncCsvWriter::with_config(Vec::new(), config);
+        writer.write_row(["1", "2"]).await.unwrap();
+        drop(writer);
+        assert_eq!(vec![ErrorKind::Io], *reported.lock().unwrap());
+
+        let sink = reported.clone();
+        let config =
+            CsvWriterConfig::default().report_on_drop(move |e| sink.lock().unwrap().push(e.kind()));
+        let mut writer = AsyncCsvWriter::with_config(Vec::new(), config);
+        writer.write_row(["1", "2"]).await.unwrap();
+        assert_eq!(b"1,2", writer.into_inner().await.unwrap().as_slice());
+        assert_eq!(1, reported.lock().unwrap().len());
+    }
 }
@@ -1,83 +1,330 @@
-use nom::{Parser, bytes::complete::tag, multi::separated_list1};
-
-pub type ParseResult<'a, T> = nom::IResult<&'a str, T>;
-
-fn textdata<F>(stop: F) -> impl FnMut(&str) -> ParseResult<&str>
-where
-    F: Fn(char) -> bool,
-{
-    move |src| {
-        for (i, c) in src.char_indices() {
-            if stop(c) {
-                return Ok((&src[i..], &src[..i]));
-            }
+use crate::ByteRecord;
+
+/// Incremental CSV parser. It is fed with a record line by line and keeps its state between lines,
+/// so the lines of a long multi-line record are scanned only once.
+/// Separator, quote and escape must not contain line ends, so they are never split between lines.
+pub(crate) struct Parser {
+    comma: Vec<u8>,
+    dquote: Vec<u8>,
+    escape: Vec<u8>,
+    /// Trim whitespace around unquoted fields of the current record
+    pub trim: bool,
+    /// Skip whitespace before the opening quote and after the closing quote of the current record
+    pub skip_quote_space: bool,
+    /// Fields are not kept, the record is only scanned for its end
+    discard: bool,
+    state: State,
+    /// Content of the field being parsed
+    field: Vec<u8>,
+    /// Offset of the field being parsed from the start of the record
+    field_start: usize,
+    /// Number of bytes of the record fed so far
+    consumed: usize,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum State {
+    /// Beginning of a field
+    StartField,
+    /// Inside an unquoted field
+    Unquoted,
+    /// Inside a quoted field
+    Quoted,
+    /// Escape character is met inside a quoted field, the next byte is a part of the field
+    Escaped,
+    /// Quote is met inside a quoted field while quotes are escaped by doubling,
+    /// it is either closing or doubled
+    QuoteInQuoted,
+    /// After the closing quote
+    AfterQuote,
+}
+
+/// Malformed record
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Malformed {
+    /// Closing quote of the field starting at the offset from the record start is followed by garbage
+    GarbageAfterQuote(usize),
+    /// Stream ended inside a quoted field
+    UnterminatedQuote,
+}
+
+impl Parser {
+    /// Create parser for the separator, quote and escape.
+    /// If `escape` is the same as `dquote` escaped quotes are doubled as in RFC 4180,
+    /// otherwise `escape` makes the next character a part of the field, e.g. `\"` or `\\`.
+    /// Empty separator makes the whole line a single field.
+    pub fn new(comma: &str, dquote: char, escape: char) -> Self {
+        Self {
+            comma: comma.as_bytes().to_vec(),
+            dquote: dquote.to_string().into_bytes(),
+            escape: escape.to_string().into_bytes(),
+            trim: false,
+            skip_quote_space: false,
+            discard: false,
+            state: State::StartField,
+            field: Vec::new(),
+            field_start: 0,
+            consumed: 0,
         }
-        Ok(("", src))
     }
-}
 
-fn escaped(comma: char, dquote: char) -> impl FnMut(&str) -> ParseResult<&str> {
-    move |src| {
-        let trimmed = src.trim_start();
-        let (rest, _) = tag(format!("{}", dquote).as_str())(trimmed)?;
-        println!("Escaped: {:?}; trimmed={:?}; rest={:?}", src, trimmed, rest);
-        let mut char_indices = rest.char_indices().peekable();
-        while let Some((i, c)) = char_indices.next() {
-            if c == dquote {
-                match char_indices.peek().copied() {
-                    Some((j, c)) if c != dquote => {
-                        let remainder = rest[j..].trim_start();
-                        let next_byte = remainder.as_bytes().first().copied().unwrap_or_default();
-                        if remainder.starts_with(comma) || next_byte < 0x20 {
-                            return Ok((remainder, &rest[..i]));
+    /// Forget the partially parsed record
+    pub fn reset(&mut self) {
+        self.discard = false;
+        self.state = State::StartField;
+        self.field.clear();
+        self.field_start = 0;
+        self.consumed = 0;
+    }
+
+    /// Parse the next line of the record appending complete fields to `record`.
+    /// Returns `true` if the record is complete, the rest of the line after its end is ignored.
+    /// Whitespace around the quotes is skipped if `skip_quote_space` is set, otherwise the quote must start
+    /// the field and whitespace after the closing quote is an error. Unquoted fields are trimmed if `trim`
+    /// is set, quotes inside them are a part of the data.
+    pub fn parse(&mut self, line: &[u8], record: &mut ByteRecord) -> Result<bool, Malformed> {
+        let result = self
+            .parse_line(line, line.len(), record)
+            .map(|(end, _)| end);
+        match result {
+            Ok(false) => self.consumed += line.len(),
+            _ => self.reset(),
+        }
+        result
+    }
+
+    /// Scan the next piece of the record without keeping its fields, e.g. the rest of a record exceeding a limit.
+    /// Only the first `stop` bytes of `piece` are scanned, the rest is the lookahead for a separator or a quote
+    /// split between pieces. Returns number of scanned bytes and whether the record ends on the line,
+    /// the rest of the line after its end is ignored. A malformed record ends on the line as well.
+    pub fn skip(&mut self, piece: &[u8], stop: usize) -> (usize, bool) {
+        self.discard = true;
+        match self.parse_line(piece, stop, &mut ByteRecord::new()) {
+            Ok((end, scanned)) => (scanned, end),
+            Err(_) => (stop, true),
+        }
+    }
+
+    /// Number of bytes a separator or a quote may take after the first one
+    pub fn lookahead(&self) -> usize {
+        [&self.comma, &self.dquote, &self.escape]
+            .iter()
+            .map(|token| token.len().saturating_sub(1))
+            .max()
+            .unwrap_or_default()
+    }
+
+    /// Complete the record at the end of the stream
+    pub fn finish(&mut self, record: &mut ByteRecord) -> Result<(), Malformed> {
+        let result = match self.state {
+            State::Quoted | State::Escaped => Err(Malformed::UnterminatedQuote),
+            _ => {
+                self.end_field(record);
+                Ok(())
+            }
+        };
+        self.reset();
+        result
+    }
+
+    /// Parse `line` up to `stop`, returns whether the record ends on the line and number of parsed bytes
+    fn parse_line(
+        &mut self,
+        line: &[u8],
+        stop: usize,
+        record: &mut ByteRecord,
+    ) -> Result<(bool, usize), Malformed> {
+        let mut i = 0;
+        while i < stop {
+            let rest = &line[i..];
+            match self.state {
+                State::StartField => {
+                    self.field_start = self.consumed + i;
+                    let space = if self.trim || self.skip_quote_space {
+                        self.skip_space(rest)
+                    } else {
+                        0
+                    };
+                    // whitespace may go on in the next piece, so it is skipped before looking for a quote
+                    if self.discard && self.skip_quote_space && space > 0 {
+                        i += space;
+                        continue;
+                    }
+                    if self.skip_quote_space && rest[space..].starts_with(&self.dquote) {
+                        i += space + self.dquote.len();
+                        self.state = State::Quoted;
+                    } else if rest.starts_with(&self.dquote) {
+                        i += self.dquote.len();
+                        self.state = State::Quoted;
+                    } else {
+                        if self.trim {
+                            i += space;
+                        }
+                        self.state = State::Unquoted;
+                    }
+                }
+                State::Unquoted => {
+                    if is_line_end(rest[0]) {
+                        self.end_field(record);
+                        return Ok((true, i));
+                    } else if self.is_comma(rest) {
+                        self.end_field(record);
+                        i += self.comma.len();
+                    } else {
+                        self.keep(&rest[..1]);
+                        i += 1;
+                    }
+                }
+                State::Quoted => {
+                    if self.escape != self.dquote && rest.starts_with(&self.escape) {
+                        i += self.escape.len();
+                        self.state = State::Escaped;
+                    } else if rest.starts_with(&self.dquote) {
+                        i += self.dquote.len();
+                        self.state = if self.escape == self.dquote {
+                            State::QuoteInQuoted
                         } else {
-                            return Err(nom::Err::Failure(nom::error::make_error(
-                                src,
-                                nom::error::ErrorKind::Fail,
-                            )));
+                            State::AfterQuote
+                        };
+                    } else {
+                        self.keep(&rest[..1]);
+                        i += 1;
+                    }
+                }
+                State::Escaped => {
+                    self.keep(&rest[..1]);
+                    i += 1;
+                    self.state = State::Quoted;
+                }
+                State::QuoteInQuoted => {
+                    if rest.starts_with(&self.dquote) {
+                        if !self.discard {
+                            self.field.extend_from_slice(&self.dquote);
                         }
+                        i += self.dquote.len();
+                        self.state = State::Quoted;
+                    } else {
+                        self.state = State::AfterQuote;
+                    }
+                }
+                State::AfterQuote => {
+                    if self.skip_quote_space {
+                        i += self.skip_space(rest);
                     }
-                    None => return Ok(("", &rest[..i])),
-                    _ => {
-                        let _ = char_indices.next();
+                    if i >= stop {
+                        break;
+                    }
+                    if is_line_end(line[i]) || self.comma.is_empty() {
+                        self.end_field(record);
+                        return Ok((true, i));
+                    } else if self.is_comma(&line[i..]) {
+                        self.end_field(record);
+                        i += self.comma.len();
+                    } else {
+                        return Err(Malformed::GarbageAfterQuote(self.field_start));
                     }
                 }
             }
         }
-        Err(nom::Err::Incomplete(nom::Needed::Unknown))
+        Ok((false, i))
     }
-}
 
-fn field<'a>(
-    comma: char,
-    dquote: char,
-) -> impl Parser<&'a str, Output = String, Error = nom::error::Error<&'a str>> {
-    let stop = move |c| (c < ' ' || c == comma || c == dquote);
-    nom::combinator::map(
-        nom::branch::alt((escaped(comma, dquote), textdata(stop))),
-        move |field| {
-            field.replace(
-                format!("{}{}", dquote, dquote).as_str(),
-                format!("{}", dquote).as_str(),
-            )
-        },
-    )
+    /// Append bytes to the field being parsed unless fields are discarded
+    fn keep(&mut self, bytes: &[u8]) {
+        if !self.discard {
+            self.field.extend_from_slice(bytes);
+        }
+    }
+
+    /// Push the field being parsed into `record`
+    fn end_field(&mut self, record: &mut ByteRecord) {
+        if !self.discard {
+            if self.trim && self.state == State::Unquoted {
+                record.push_field(self.field.trim_ascii_end());
+            } else {
+                record.push_field(&self.field);
+            }
+        }
+        self.field.clear();
+        self.state = State::StartField;
+    }
+
+    fn is_comma(&self, rest: &[u8]) -> bool {
+        !self.comma.is_empty() && rest.starts_with(&self.comma)
+    }
+
+    /// Number of whitespace bytes at the start of `rest` which are neither line ends nor the separator,
+    /// e.g. '\t'
+    fn skip_space(&self, rest: &[u8]) -> usize {
+        let mut i = 0;
+        while i < rest.len()
+            && rest[i].is_ascii_whitespace()
+            && !is_line_end(rest[i])
+            && !self.is_comma(&rest[i..])
+        {
+            i += 1;
+        }
+        i
+    }
 }
 
-pub fn record(src: &str, comma: char, dquote: char) -> ParseResult<Vec<String>> {
-    separated_list1(tag(format!("{}", comma).as_str()), field(comma, dquote)).parse(src)
+fn is_line_end(byte: u8) -> bool {
+    byte == b'\r' || byte == b'\n'
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    fn fields(src: &str, comma: char, dquote: char) -> Result<Vec<String>, Malformed> {
+        parse_fields(src, comma, dquote, dquote, true)
+    }
+
+    fn escaped_fields(
+        src: &str,
+        comma: char,
+        dquote: char,
+        escape: char,
+    ) -> Result<Vec<String>, Malformed> {
+        parse_fields(src, comma, dquote, escape, true)
+    }
+
+    fn parse_fields(
+        src: &str,
+        comma: char,
+        dquote: char,
+        escape: char,
+        trim: bool,
+    ) -> Result<Vec<String>, Malformed> {
+        parse_with(src, &comma.to_string(), dquote, escape, trim)
+    }
+
+    fn parse_with(
+        src: &str,
+        comma: &str,
+        dquote: char,
+        escape: char,
+        trim: bool,
+    ) -> Result<Vec<String>, Malformed> {
+        let mut fields = ByteRecord::new();
+        let mut parser = Parser::new(comma, dquote, escape);
+        parser.trim = trim;
+        parser.skip_quote_space = trim;
+        if !parser.parse(src.as_bytes(), &mut fields)? {
+            parser.finish(&mut fields)?;
+        }
+        Ok(fields
+            .iter()
+            .map(|field| String::from_utf8(field.to_vec()).unwrap())
+            .collect())
+    }
+
     #[test]
     fn parse_just_record() {
         let line = "мама,мыла,раму\r\n";
         assert_eq!(
             vec!["мама", "мыла", "раму"],
-            record(line, ',', '"').unwrap().1
+            fields(line, ',', '"').unwrap()
         );
     }
 
@@ -86,7 +333,7 @@ mod tests {
         let line = "мама, \"мыла\",раму";
         assert_eq!(
             vec!["мама", "мыла", "раму"],
-            record(line, ',', '"').unwrap().1
+            fields(line, ',', '"').unwrap()
         );
     }
 
@@ -95,7 +342,7 @@ mod tests {
         let line = "мама, \"мыла\ntwo times\"\t\t,раму";
         assert_eq!(
             vec!["мама", "мыла\ntwo times", "раму"],
-            record(line, ',', '"').unwrap().1
+            fields(line, ',', '"').unwrap()
         );
     }
 
@@ -103,11 +350,13 @@ mod tests {
     fn fail_after_dquote() {
         let comma = ',';
         let dquote = '"';
-        println!("{:?}", record("мама,мыла, \"раму\"abc", comma, dquote));
-        assert!(record("мама,мыла, \"раму\"abc", comma, dquote).is_err());
+        assert_eq!(
+            Err(Malformed::GarbageAfterQuote(18)),
+            fields("мама,мыла, \"раму\"abc", comma, dquote)
+        );
         assert_eq!(
             vec!["мама", "мыла", "раму"],
-            record("мама,\"мыла\", \"раму\" ", comma, dquote).unwrap().1
+            fields("мама,\"мыла\", \"раму\" ", comma, dquote).unwrap()
         );
     }
 
@@ -116,7 +365,85 @@ mod tests {
         let line = "мама, \"мыла\n\"\"two times\"\"\"\t\t,раму";
         assert_eq!(
             vec!["мама", "мыла\n\"two times\"", "раму"],
-            record(line, ',', '"').unwrap().1
+            fields(line, ',', '"').unwrap()
+        );
+    }
+
+    #[test]
+    fn resume_between_lines() {
+        let mut parser = Parser::new(",", '"', '"');
+        let mut record = ByteRecord::new();
+        assert_eq!(Ok(false), parser.parse(b"1,\"two\r\n", &mut record));
+        assert_eq!(vec![&b"1"[..]], record.iter().collect::<Vec<_>>());
+        assert_eq!(Ok(false), parser.parse(b"\"\"lines\"\"\n", &mut record));
+        assert_eq!(
+            Err(Malformed::GarbageAfterQuote(2)),
+            parser.parse(b"\"x\r\n", &mut record)
+        );
+        record.clear();
+        assert_eq!(Ok(true), parser.parse(b"\"a\"\"\",b\r\n", &mut record));
+        assert_eq!(vec![&b"a\""[..], b"b"], record.iter().collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn backslash_escaped() {
+        let line = "мама,\"мыла \\\"раму\\\" \\\\ \\n\",'\\'\r\n";
+        assert_eq!(
+            vec!["мама", "мыла \"раму\" \\ n", "'\\'"],
+            escaped_fields(line, ',', '"', '\\').unwrap()
+        );
+        assert_eq!(
+            vec!["мама", "мыла", "раму"],
+            escaped_fields("мама;'мыла';'раму'", ';', '\'', '\\').unwrap()
+        );
+        assert_eq!(
+            Err(Malformed::UnterminatedQuote),
+            escaped_fields("не,\"дочитано \\\"\n", ',', '"', '\\')
+        );
+    }
+
+    #[test]
+    fn string_separator() {
+        assert_eq!(
+            vec!["a", "b||c", " d|e ", ""],
+            parse_with("a||\"b||c\"|| d|e ||\r\n", "||", '"', '"', false).unwrap()
+        );
+        assert_eq!(
+            vec!["a", "", "x", "y"],
+            parse_with("a\t\t\"x\"\t \"y\" \n", "\t", '"', '"', true).unwrap()
+        );
+        assert_eq!(
+            vec!["a,b;c"],
+            parse_with("a,b;c\r\n", "", '"', '"', false).unwrap()
+        );
+    }
+
+    #[test]
+    fn trim_or_not() {
+        let line = " a ,\t\" b \" ,c \"d\" e, \"f\"\r\n";
+        assert_eq!(
+            vec!["a", " b ", "c \"d\" e", "f"],
+            parse_fields(line, ',', '"', '"', true).unwrap()
+        );
+        assert_eq!(
+            vec![" a ", "\t\" b \" ", "c \"d\" e", " \"f\""],
+            parse_fields(line, ',', '"', '"', false).unwrap()
+        );
+        assert_eq!(
+            Err(Malformed::GarbageAfterQuote(0)),
+            parse_fields("\"a\" ,b", ',', '"', '"', false)
+        );
+        assert_eq!(
+            vec!["a\tb", "c"],
+            parse_fields("a\tb,c", ',', '"', '"', false).unwrap()
+        );
+        let mut parser = Parser::new(",", '"', '"');
+        parser.skip_quote_space = true;
+        let mut record = ByteRecord::new();
+        assert_eq!(Ok(true), parser.parse(line.as_bytes(), &mut record));
+        assert_eq!(
+            vec![&b" a "[..], b" b ", b"c \"d\" e", b"f"],
+            record.iter().collect::<Vec<_>>()
         );
     }
 }
@@ -0,0 +1,88 @@
+/// Location inside a CSV stream.
+///
+/// Records and lines are counted from 1, headers are the first record if the stream has them.
+/// Column is counted in characters from 1, byte offset is counted from the start of the stream.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Position {
+    /// Record number
+    pub record: u64,
+    /// Physical line number
+    pub line: u64,
+    /// Column number on the line
+    pub column: u64,
+    /// Byte offset from the beginning of the stream
+    pub byte: u64,
+}
+
+impl Position {
+    /// Position of the very first record in a stream
+    pub fn new() -> Self {
+        Self {
+            record: 1,
+            line: 1,
+            column: 1,
+            byte: 0,
+        }
+    }
+
+    /// Move position forward over `text`. Column counts characters for UTF-8 text,
+    /// bytes of invalid sequences are counted one by one.
+    pub(crate) fn advance(&mut self, text: impl AsRef<[u8]>) {
+        for &b in text.as_ref() {
+            self.byte += 1;
+            if b == b'\n' {
+                self.line += 1;
+                self.column = 1;
+            } else if !is_continuation(b) {
+                self.column += 1;
+            }
+        }
+    }
+
+    /// Position after `text` is passed from `self`
+    pub(crate) fn advanced(mut self, text: impl AsRef<[u8]>) -> Self {
+        self.advance(text);
+        self
+    }
+
+    /// Start of a span of text, advance it over the text to [shift](Position::shift) positions by the span
+    pub(crate) fn span() -> Self {
+        Self {
+            record: 0,
+            line: 0,
+            column: 0,
+            byte: 0,
+        }
+    }
+
+    /// Move position forward over the text of `span` starting at the position, records are not counted
+    pub(crate) fn shift(&mut self, span: Position) {
+        self.byte += span.byte;
+        if span.line > 0 {
+            self.line += span.line;
+            self.column = span.column;
+        } else {
+            self.column += span.column;
+        }
+    }
+}
+
+fn is_continuation(b: u8) -> bool {
+    b & 0b1100_0000 == 0b1000_0000
+}
+
+impl Default for Position {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl std::fmt::Display for Position {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "record {}, line {}, column {}, byte {}",
+            self.record, self.line, self.column, self.byte
+        )
+    }
+}
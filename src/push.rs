@@ -0,0 +1,163 @@
+use crate::{ByteRecord, CsvReader, CsvReaderConfig, Headers, Position, Record, reader};
+use std::{
+    io::{BufRead, Read},
+    sync::Arc,
+};
+
+/// Push parser for non-blocking and network sources.
+/// Chunks of the stream are [fed](CsvPushParser::feed) as they arrive and complete records are read
+/// as soon as they are available. A record, a UTF-8 sequence or a quote split between chunks is kept
+/// until the rest of it is fed. [`CsvPushParser::finish`] marks the end of the stream,
+/// so the last record is read even without a line terminator.
+/// The parser has the same [options](CsvReaderConfig) as [`CsvReader`].
+///
+/// # Example
+///
+/// ```
+/// let mut parser = justcsv::CsvPushParser::new();
+/// parser.feed(b"1,\"two\n");
+/// assert!(parser.next().is_none());
+/// let rest = "lines\",три\r\n4,5".as_bytes();
+/// // the chunk ends inside of "р"
+/// parser.feed(&rest[..10]);
+/// assert!(parser.next().is_none());
+/// parser.feed(&rest[10..]);
+/// let record = parser.next().unwrap().unwrap();
+/// assert_eq!(vec!["1", "two\nlines", "три"], record.iter().collect::<Vec<_>>());
+/// assert!(parser.next().is_none());
+/// parser.finish();
+/// let record = parser.next().unwrap().unwrap();
+/// assert_eq!(vec!["4", "5"], record.iter().collect::<Vec<_>>());
+/// assert!(parser.next().is_none());
+/// ```
+pub struct CsvPushParser {
+    reader: CsvReader<Feed>,
+}
+
+impl CsvPushParser {
+    /// Create parser with default options
+    pub fn new() -> Self {
+        Self::with_config(Default::default())
+    }
+
+    /// Create parser with options passed as [config](CsvReaderConfig)
+    pub fn with_config(config: CsvReaderConfig) -> Self {
+        Self {
+            reader: CsvReader::with_config(Feed::default(), config),
+        }
+    }
+
+    /// Append the next chunk of the stream
+    pub fn feed(&mut self, chunk: &[u8]) {
+        self.reader.source_mut().push(chunk);
+        self.reader.try_start();
+    }
+
+    /// Mark the end of the stream, the rest of it is read as the last record
+    pub fn finish(&mut self) {
+        self.reader.source_mut().finished = true;
+        self.reader.try_start();
+    }
+
+    /// Returns CSV headers if they are expected and fed already
+    pub fn headers(&self) -> Option<&[String]> {
+        self.reader.headers()
+    }
+
+    /// Returns CSV headers with index of columns by name, it is shared with records the parser yields
+    pub fn header_index(&self) -> Option<&Arc<Headers>> {
+        self.reader.header_index()
+    }
+
+    /// Lines skipped at the start of the stream, see [`CsvReader::preamble`]
+    pub fn preamble(&self) -> &[String] {
+        self.reader.preamble()
+    }
+
+    /// Comment lines read so far, see [`CsvReader::comments`]
+    pub fn comments(&self) -> &[String] {
+        self.reader.comments()
+    }
+
+    /// Position of the next record in the stream
+    pub fn position(&self) -> Position {
+        self.reader.position()
+    }
+
+    /// Read next complete record into `record` reusing its memory.
+    /// Returns `false` if more chunks are needed or the stream is complete.
+    pub fn read_record(&mut self, record: &mut Record) -> crate::Result<bool> {
+        match self.reader.read_record(record) {
+            Err(e) if reader::would_block(&e) => Ok(false),
+            result => result,
+        }
+    }
+
+    /// Read next complete record into `record` as raw bytes, fields are not checked to be UTF-8.
+    /// Returns `false` if more chunks are needed or the stream is complete.
+    pub fn read_byte_record(&mut self, record: &mut ByteRecord) -> crate::Result<bool> {
+        match self.reader.read_byte_record(record) {
+            Err(e) if reader::would_block(&e) => Ok(false),
+            result => result,
+        }
+    }
+}
+
+impl Default for CsvPushParser {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Yields complete records fed so far, then `None` until more chunks are fed
+impl Iterator for CsvPushParser {
+    type Item = crate::Result<Record>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let mut record = Record::new();
+        match self.read_record(&mut record) {
+            Ok(true) => Some(Ok(record)),
+            Ok(false) => None,
+            Err(e) => Some(Err(e)),
+        }
+    }
+}
+
+/// Chunks fed to the parser. Reading would block when they run out unless the stream is finished.
+#[derive(Debug, Default)]
+struct Feed {
+    buf: Vec<u8>,
+    pos: usize,
+    finished: bool,
+}
+
+impl Feed {
+    fn push(&mut self, chunk: &[u8]) {
+        self.buf.drain(..self.pos);
+        self.pos = 0;
+        self.buf.extend_from_slice(chunk);
+    }
+}
+
+impl Read for Feed {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        let available = self.fill_buf()?;
+        let n = available.len().min(buf.len());
+        buf[..n].copy_from_slice(&available[..n]);
+        self.consume(n);
+        Ok(n)
+    }
+}
+
+impl BufRead for Feed {
+    fn fill_buf(&mut self) -> std::io::Result<&[u8]> {
+        if self.pos == self.buf.len() && !self.finished {
+            return Err(std::io::ErrorKind::WouldBlock.into());
+        }
+        Ok(&self.buf[self.pos..])
+    }
+
+    fn consume(&mut self, amt: usize) {
+        self.pos += amt;
+    }
+}
@@ -1,10 +1,24 @@
-use crate::parse;
-use std::io::BufRead;
+use crate::{
+    ByteRecord, Headers, Position, Record, Schema, Value, de,
+    encoding::Decoded,
+    parse::{self, Parser},
+};
+use serde::de::DeserializeOwned;
+use std::{
+    collections::VecDeque,
+    io::{BufRead, Read},
+    marker::PhantomData,
+    sync::Arc,
+};
 
-pub use config::Config as CsvReaderConfig;
+pub use config::{
+    Config as CsvReaderConfig, FieldCount, HeaderNames, Limit, Quarantine, Recovery, Trim,
+};
 
 /// CSV reader for UTF-8 comma separated content.
 /// Use it as an iterator to read records line by line.
+/// Content in other encodings may be decoded with respect to [config](CsvReaderConfig::encoding)
+/// or read with [`CsvReader::read_byte_record`].
 ///
 /// # Example
 ///
@@ -22,9 +36,64 @@ pub use config::Config as CsvReaderConfig;
 /// assert!(reader.next().is_none());
 /// ```
 pub struct CsvReader<R> {
-    source: R,
+    source: Decoded<R>,
     config: CsvReaderConfig,
-    headers: Option<Box<[String]>>,
+    headers: Option<Arc<Headers>>,
+    header_error: Option<crate::Error>,
+    position: Position,
+    record_line: Vec<u8>,
+    byte_record: ByteRecord,
+    field_count: Option<usize>,
+    preamble: Vec<String>,
+    comments: Vec<String>,
+    /// Lines left after the quarantined one by [`Recovery::Resync`], they are read before the source
+    pending: VecDeque<u8>,
+    aborted: bool,
+    parser: Parser,
+    /// Preamble and headers are read
+    started: bool,
+    /// Record being read when the source would block
+    partial: Option<Partial>,
+    /// Start of the last record read
+    record_start: Position,
+    /// Input to skip before the next record
+    skip: Skip,
+    /// Bytes of a record exceeding a limit which are read but not scanned yet
+    skipped: Vec<u8>,
+    /// Lines dropped by [`Recovery::Resync`] after the lines pushed back into `pending`, the nearest is the last
+    gaps: Vec<Gap>,
+}
+
+/// Input to skip before the next record
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Skip {
+    Nothing,
+    /// Rest of the physical line after a comment cut by the record size limit or after a skipped record
+    Line,
+    /// Rest of the record exceeding a limit, it is scanned without keeping so memory stays bounded
+    Record,
+}
+
+/// Line cut by the record size limit and dropped by [`Recovery::Resync`]
+#[derive(Debug, Clone, Copy)]
+struct Gap {
+    /// Number of bytes in `pending` after the gap, the lines before it are read first
+    after: usize,
+    /// Span of the line dropped so far
+    span: Position,
+}
+
+/// Size of pieces the rest of a record exceeding a limit is scanned by
+const SKIP_PIECE: usize = 8 * 1024;
+
+/// State of the record being read, it is kept if the source would block to resume reading later
+#[derive(Debug, Clone, Copy)]
+struct Partial {
+    start: Position,
+    /// Number of physical lines read
+    lines: usize,
+    /// Number of bytes of `record_line` fed to the parser
+    parsed: usize,
 }
 
 impl<R: BufRead> CsvReader<R> {
@@ -34,27 +103,810 @@ impl<R: BufRead> CsvReader<R> {
     }
 
     /// Create reader with options passed as [config](CsvReaderConfig)
-    pub fn with_config(mut source: R, config: CsvReaderConfig) -> Self {
-        let headers = if config.has_headers {
-            // if parsing headers fails self.headers is None but self.config.has_headers is still true
-            load_headers(&mut source, config.separator, config.escape)
-        } else {
-            None
-        };
-        Self {
-            source,
+    pub fn with_config(source: R, config: CsvReaderConfig) -> Self {
+        let mut reader = Self {
+            source: Decoded::new(source, config.encoding),
+            headers: None,
+            header_error: None,
+            position: Position::new(),
+            record_line: Vec::new(),
+            byte_record: ByteRecord::new(),
+            field_count: None,
+            preamble: Vec::new(),
+            comments: Vec::new(),
+            pending: VecDeque::new(),
+            aborted: false,
+            parser: Parser::new(&config.separator, config.quote, config.escape),
+            started: false,
+            partial: None,
+            record_start: Position::new(),
+            skip: Skip::Nothing,
+            skipped: Vec::new(),
+            gaps: Vec::new(),
             config,
-            headers,
-        }
+        };
+        reader.try_start();
+        reader
     }
 
     /// Returns CSV headers if they are expected for the stream
     pub fn headers(&self) -> Option<&[String]> {
-        self.headers.as_deref()
+        self.headers.as_deref().map(Headers::names)
+    }
+
+    /// Returns CSV headers with index of columns by name, it is shared with records the reader yields
+    pub fn header_index(&self) -> Option<&Arc<Headers>> {
+        self.headers.as_ref()
+    }
+
+    /// Lines skipped at the start of the stream with respect to [config](CsvReaderConfig::skip_lines),
+    /// line terminators are removed
+    pub fn preamble(&self) -> &[String] {
+        &self.preamble
+    }
+
+    /// Comment lines read so far without the [prefix](CsvReaderConfig::comment) and line terminators
+    pub fn comments(&self) -> &[String] {
+        &self.comments
+    }
+
+    /// Position of the next record in the stream
+    pub fn position(&self) -> Position {
+        self.position
+    }
+
+    /// Iterate through records which may be accessed by header names
+    pub fn records(&mut self) -> Records<'_, R> {
+        Records { reader: self }
+    }
+
+    /// Read next record into `record` reusing its memory.
+    /// Returns `false` if the stream is complete.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// let buf = "1,\"two \"\"quoted\"\"\",3\r\n4,5,6".as_bytes();
+    /// let mut reader = justcsv::CsvReader::new(buf);
+    /// let mut record = justcsv::Record::new();
+    /// assert!(reader.read_record(&mut record).unwrap());
+    /// assert_eq!("two \"quoted\"", &record[1]);
+    /// assert!(reader.read_record(&mut record).unwrap());
+    /// assert_eq!(vec!["4", "5", "6"], record.iter().collect::<Vec<_>>());
+    /// assert!(!reader.read_record(&mut record).unwrap());
+    /// ```
+    pub fn read_record(&mut self, record: &mut Record) -> crate::Result<bool> {
+        match self.read_into(record) {
+            Ok(()) => Ok(true),
+            Err(crate::Error::StreamComplete) => Ok(false),
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Read next record into `record` as raw bytes, fields are not checked to be UTF-8.
+    /// Returns `false` if the stream is complete.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// let buf = b"\xcc\xe0\xec\xe0,\"\xec\xfb\xeb\xe0\"\r\n";
+    /// let mut reader = justcsv::CsvReader::new(&buf[..]);
+    /// let mut record = justcsv::ByteRecord::new();
+    /// assert!(reader.read_byte_record(&mut record).unwrap());
+    /// assert_eq!(b"\xec\xfb\xeb\xe0", &record[1]);
+    /// ```
+    pub fn read_byte_record(&mut self, record: &mut ByteRecord) -> crate::Result<bool> {
+        match self.start().and_then(|()| self.next_byte_record()) {
+            Ok(_) => {
+                std::mem::swap(record, &mut self.byte_record);
+                Ok(true)
+            }
+            Err(crate::Error::StreamComplete) => Ok(false),
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Iterate through records deserialized into `T`.
+    /// Struct fields are matched by headers if the stream has them, by position otherwise.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// #[derive(serde::Deserialize)]
+    /// struct Row {
+    ///     name: String,
+    ///     amount: f64,
+    /// }
+    ///
+    /// let buf = "amount,name\r\n1.5,milk".as_bytes();
+    /// let config = justcsv::CsvReaderConfig::default().has_headers(true);
+    /// let mut reader = justcsv::CsvReader::with_config(buf, config);
+    /// let row: Row = reader.deserialize().next().unwrap().unwrap();
+    /// assert_eq!("milk", row.name);
+    /// assert_eq!(1.5, row.amount);
+    /// ```
+    pub fn deserialize<T: DeserializeOwned>(&mut self) -> DeserializeRecords<'_, R, T> {
+        DeserializeRecords {
+            reader: self,
+            record: Record::new(),
+            _record: PhantomData,
+        }
+    }
+
+    /// Iterator over records converted into typed values of the [schema](Schema) columns.
+    /// See [`Schema`] for an example.
+    pub fn typed<'a>(&'a mut self, schema: &'a Schema) -> TypedRecords<'a, R> {
+        TypedRecords {
+            reader: self,
+            schema,
+            record: Record::new(),
+        }
     }
 
     fn next_row(&mut self) -> crate::Result<Box<[String]>> {
-        parse_row(&mut self.source, self.config.separator, self.config.escape)
+        let mut record = Record::new();
+        self.read_into(&mut record)?;
+        Ok(record.to_boxed_slice())
+    }
+
+    /// Mutable reference to the underlying source
+    pub(crate) fn source_mut(&mut self) -> &mut R {
+        self.source.get_mut()
+    }
+
+    /// Skip the preamble and load headers unless the source would block, then the first read does it.
+    /// If parsing headers fails self.headers is None but self.config.has_headers is still true,
+    /// the error is returned by the first read.
+    pub(crate) fn try_start(&mut self) {
+        if let Err(e) = self.start()
+            && !would_block(&e)
+        {
+            self.header_error = Some(e);
+        }
+    }
+
+    /// Skip the preamble and load headers before the first record, returns the error of headers once
+    fn start(&mut self) -> crate::Result<()> {
+        if let Some(e) = self.header_error.take() {
+            return Err(e);
+        }
+        if self.started {
+            return Ok(());
+        }
+        let result = self.skip_preamble().and_then(|()| {
+            if self.config.has_headers {
+                self.headers = Some(Arc::new(self.load_headers()?));
+            }
+            Ok(())
+        });
+        // a blocked source is read from the same place later, any other failure is not retried
+        self.started = !matches!(&result, Err(e) if would_block(e));
+        result
+    }
+
+    fn skip_preamble(&mut self) -> crate::Result<()> {
+        // a line is kept in `record_line` until it is read completely
+        while self.preamble.len() < self.config.skip_lines {
+            let n = read_line(
+                &mut self.source,
+                &mut self.pending,
+                &mut self.record_line,
+                usize::MAX,
+            )
+            .map_err(|source| crate::Error::ReadFailed {
+                position: self.position,
+                source,
+            })?;
+            if n == 0 && self.record_line.is_empty() {
+                break;
+            }
+            if n != 0 && !self.record_line.ends_with(b"\n") {
+                continue;
+            }
+            self.position.advance(&self.record_line);
+            self.preamble.push(line_text(&self.record_line));
+            self.record_line.clear();
+        }
+        Ok(())
+    }
+
+    fn load_headers(&mut self) -> crate::Result<Headers> {
+        let start = self.position;
+        let mut record = Record::new();
+        self.next_record(&mut record)?;
+        let names = record.to_boxed_slice();
+        Headers::new(names.clone(), self.config.header_names).map_err(|column| {
+            crate::Error::InvalidHeader {
+                position: start,
+                column,
+                name: names[column].clone(),
+            }
+        })
+    }
+
+    fn read_into(&mut self, record: &mut Record) -> crate::Result<()> {
+        self.start()?;
+        self.next_record(record)
+    }
+
+    /// Read next record into `record` converting it into UTF-8
+    fn next_record(&mut self, record: &mut Record) -> crate::Result<()> {
+        record.share_headers(self.headers.as_ref());
+        loop {
+            let start = self.next_byte_record()?;
+            record.set_position(start);
+            if self.config.lossy_utf8 {
+                self.byte_record.to_record_lossy(record);
+                return Ok(());
+            }
+            let Err(_) = self.byte_record.to_record(record) else {
+                return Ok(());
+            };
+            let valid =
+                std::str::from_utf8(&self.record_line).map_or_else(|e| e.valid_up_to(), |_| 0);
+            let e = crate::Error::InvalidUtf8(start.advanced(&self.record_line[..valid]));
+            // the record is parsed already so there is nothing to resynchronize
+            if let Some(e) = self.recover(e, start, false) {
+                return Err(e);
+            }
+        }
+    }
+
+    /// Read next record into `byte_record` with respect to the [recovery policy](Recovery),
+    /// returns the record position
+    fn next_byte_record(&mut self) -> crate::Result<Position> {
+        loop {
+            if self.aborted {
+                return Err(crate::Error::StreamComplete);
+            }
+            match self.parse_record() {
+                Err(e) if is_malformed(&e) => {
+                    let resync = is_unbounded(&e);
+                    let e = self.recover(e, self.record_start, resync);
+                    // the rest of a record exceeding a limit is skipped now, so the position is the next record.
+                    // If the source would block the next read skips it.
+                    if let Err(e) = self.skip_rest()
+                        && !would_block(&e)
+                    {
+                        return Err(e);
+                    }
+                    if let Some(e) = e {
+                        return Err(e);
+                    }
+                }
+                result => return result,
+            }
+        }
+    }
+
+    /// Apply the [recovery policy](Recovery) to the error of the record in `record_line` starting at `start`.
+    /// Returns the error if it goes to the caller, otherwise the record is quarantined.
+    fn recover(&mut self, e: crate::Error, start: Position, resync: bool) -> Option<crate::Error> {
+        if self.is_header(e.position().unwrap_or(start)) {
+            return Some(e);
+        }
+        match self.config.recovery {
+            Recovery::Return => return Some(e),
+            Recovery::Abort => {
+                self.aborted = true;
+                return Some(e);
+            }
+            Recovery::Skip => {}
+            Recovery::Resync if !resync => {}
+            Recovery::Resync => {
+                let (first_line, _) = line_end(&self.record_line);
+                // a line cut by the record size limit is dropped with the rest of it,
+                // only complete lines after the first one are read again
+                let cut = self.skip == Skip::Record && !self.record_line.ends_with(b"\n");
+                let end = if cut {
+                    line_start(&self.record_line).max(first_line)
+                } else {
+                    self.record_line.len()
+                };
+                if cut {
+                    self.gaps.push(Gap {
+                        after: self.pending.len(),
+                        span: Position::span().advanced(&self.record_line[end..]),
+                    });
+                }
+                for &b in self.record_line[first_line..end].iter().rev() {
+                    self.pending.push_front(b);
+                }
+                self.record_line.truncate(first_line);
+                self.position = start.advanced(&self.record_line);
+                self.position.record = start.record + 1;
+                self.skip = Skip::Nothing;
+                self.skipped.clear();
+            }
+        }
+        if let Some(sink) = self.config.quarantine.as_mut() {
+            sink(&self.record_line, e);
+        }
+        None
+    }
+
+    /// Headers are the first record of the stream
+    fn is_header(&self, position: Position) -> bool {
+        self.config.has_headers && position.record == 1
+    }
+
+    /// Read and parse next record into `byte_record`, returns the record position.
+    /// If the source would block the record read so far is kept and the next call resumes it.
+    fn parse_record(&mut self) -> crate::Result<Position> {
+        let mut partial = match self.partial.take() {
+            Some(partial) => partial,
+            None => {
+                // the parser keeps the state of the record being skipped until it ends
+                self.skip_rest()?;
+                let start = self.position;
+                // headers are the first record of the stream
+                let trim = match self.config.trim {
+                    Trim::None | Trim::Quotes => false,
+                    Trim::Headers => self.is_header(start),
+                    Trim::Fields => !self.is_header(start),
+                    Trim::All => true,
+                };
+                self.parser.trim = trim;
+                self.parser.skip_quote_space = trim || self.config.trim == Trim::Quotes;
+                self.parser.reset();
+                self.byte_record.clear();
+                self.record_line.clear();
+                Partial {
+                    start,
+                    lines: 0,
+                    parsed: 0,
+                }
+            }
+        };
+        let mut record = std::mem::take(&mut self.byte_record);
+        let result = self.parse_lines(&mut record, &mut partial);
+        self.byte_record = record;
+        if matches!(&result, Err(e) if would_block(e)) {
+            self.partial = Some(partial);
+        }
+        result
+    }
+
+    /// Read lines of the record into `record_line` feeding complete ones to the parser
+    fn parse_lines(
+        &mut self,
+        record: &mut ByteRecord,
+        partial: &mut Partial,
+    ) -> crate::Result<Position> {
+        let result = loop {
+            if partial.parsed == 0 && self.record_line.is_empty() {
+                self.skip_rest()?;
+                partial.start = self.position;
+            } else if self.at_gap() {
+                // lines pushed back by the resync end where the dropped line was, so does the record
+                break self.finish_record(record, partial.start);
+            }
+            let start = partial.start;
+            // one byte over the limit is read to find out the record exceeds it
+            let max = self.config.max_record_bytes.map_or(usize::MAX, |max| {
+                (max + 1).saturating_sub(self.record_line.len())
+            });
+            let n = match read_line(
+                &mut self.source,
+                &mut self.pending,
+                &mut self.record_line,
+                max,
+            ) {
+                Ok(n) => n,
+                // bytes read so far stay in `record_line`
+                Err(source) if source.kind() == std::io::ErrorKind::WouldBlock => {
+                    return Err(crate::Error::ReadFailed {
+                        position: start,
+                        source,
+                    });
+                }
+                Err(source) => {
+                    break Err(crate::Error::ReadFailed {
+                        position: start,
+                        source,
+                    });
+                }
+            };
+            let line = &self.record_line[partial.parsed..];
+            // the line is cut by the record size limit, its rest is skipped after the record
+            let cut = n == max && !line.ends_with(b"\n");
+            if n == 0 && line.is_empty() {
+                if self.record_line.is_empty() {
+                    return Err(crate::Error::StreamComplete);
+                }
+                break self.finish_record(record, start);
+            }
+            if n != 0 && !cut && !line.ends_with(b"\n") {
+                // the last line of the source, the next read confirms its end
+                continue;
+            }
+            if let Some(prefix) = self.config.comment.as_deref()
+                // every line starts with an empty prefix, so it is the same as no prefix
+                && !prefix.is_empty()
+                && partial.parsed == 0
+                && line.starts_with(prefix.as_bytes())
+            {
+                self.comments.push(line_text(&line[prefix.len()..]));
+                self.position.advance(&self.record_line);
+                self.record_line.clear();
+                if cut {
+                    self.skip = Skip::Line;
+                }
+                continue;
+            }
+            partial.lines += 1;
+            if let Some(max) = self.config.max_record_bytes
+                && self.record_line.len() > max
+            {
+                // the line is not parsed yet, the rest of the record is scanned starting with it
+                self.skipped.extend_from_slice(line);
+                self.skip = Skip::Record;
+                break Err(crate::Error::LimitExceeded {
+                    position: start,
+                    limit: Limit::RecordBytes,
+                    max,
+                });
+            }
+            partial.parsed = self.record_line.len();
+            match self.parser.parse(line, record) {
+                Ok(true) => break self.complete_record(record, start),
+                // source is exhausted
+                Ok(false) if n == 0 => break self.finish_record(record, start),
+                Ok(false) => {
+                    if let Some(max) = self.config.max_lines
+                        && partial.lines >= max
+                    {
+                        self.skip = Skip::Record;
+                        break Err(crate::Error::LimitExceeded {
+                            position: start,
+                            limit: Limit::Lines,
+                            max,
+                        });
+                    }
+                    //record in CSV-file may consist of several lines if has escaped fields with newlines inside
+                    continue;
+                }
+                Err(parse::Malformed::GarbageAfterQuote(offset)) => {
+                    let parsed = &self.record_line[..offset];
+                    break Err(crate::Error::GarbageAfterQuote(start.advanced(parsed)));
+                }
+                Err(parse::Malformed::UnterminatedQuote) => {
+                    break Err(crate::Error::UnterminatedQuote(start));
+                }
+            }
+        };
+        // bytes which are not scanned yet are counted when the rest of the record is skipped
+        let counted = self.record_line.len() - self.skipped.len();
+        self.position.advance(&self.record_line[..counted]);
+        self.position.record += 1;
+        self.record_start = partial.start;
+        result
+    }
+
+    /// Skip input which is not a part of the next record: the rest of a cut comment line or of a record
+    /// exceeding a limit, and lines dropped by the resync
+    fn skip_rest(&mut self) -> crate::Result<()> {
+        match self.skip {
+            Skip::Nothing => {}
+            Skip::Line => self.skip_rest_of_line()?,
+            Skip::Record => self.skip_rest_of_record()?,
+        }
+        while let Some(gap) = self.gaps.last_mut()
+            && self.pending.len() == gap.after
+        {
+            skip_line(&mut self.source, &mut self.pending, &mut gap.span).map_err(|source| {
+                crate::Error::ReadFailed {
+                    position: self.position,
+                    source,
+                }
+            })?;
+            self.position.shift(gap.span);
+            self.gaps.pop();
+        }
+        Ok(())
+    }
+
+    /// The next line is behind a line dropped by the resync
+    fn at_gap(&self) -> bool {
+        self.gaps
+            .last()
+            .is_some_and(|gap| self.pending.len() == gap.after)
+    }
+
+    /// Skip the rest of the physical line which is cut by the record size limit
+    fn skip_rest_of_line(&mut self) -> crate::Result<()> {
+        skip_line(&mut self.source, &mut self.pending, &mut self.position).map_err(|source| {
+            crate::Error::ReadFailed {
+                position: self.position,
+                source,
+            }
+        })?;
+        self.skip = Skip::Nothing;
+        Ok(())
+    }
+
+    /// Scan the rest of the record exceeding a limit by pieces without keeping it,
+    /// so reading goes on with the next record
+    fn skip_rest_of_record(&mut self) -> crate::Result<()> {
+        let piece = self
+            .config
+            .max_record_bytes
+            .map_or(SKIP_PIECE, |max| SKIP_PIECE.max(max.saturating_add(1)));
+        let lookahead = self.parser.lookahead();
+        loop {
+            let mut end = self.at_gap();
+            if !end && !self.skipped.ends_with(b"\n") && self.skipped.len() < piece {
+                let max = piece - self.skipped.len();
+                let n = read_line(&mut self.source, &mut self.pending, &mut self.skipped, max)
+                    .map_err(|source| crate::Error::ReadFailed {
+                        position: self.position,
+                        source,
+                    })?;
+                end = n == 0;
+            }
+            let complete = end || self.skipped.ends_with(b"\n");
+            let stop = if complete {
+                self.skipped.len()
+            } else {
+                self.skipped.len().saturating_sub(lookahead)
+            };
+            let (scanned, done) = self.parser.skip(&self.skipped, stop);
+            if done || end {
+                self.parser.reset();
+                self.position.advance(&self.skipped);
+                self.skipped.clear();
+                if complete {
+                    self.skip = Skip::Nothing;
+                    return Ok(());
+                }
+                // the rest of the line after the end of the record is ignored as for any record
+                self.skip = Skip::Line;
+                return self.skip_rest_of_line();
+            }
+            self.position.advance(&self.skipped[..scanned]);
+            self.skipped.drain(..scanned);
+        }
+    }
+
+    /// Complete the record at the end of the stream unless it has unterminated quote
+    fn finish_record(
+        &mut self,
+        record: &mut ByteRecord,
+        start: Position,
+    ) -> crate::Result<Position> {
+        match self.parser.finish(record) {
+            Ok(()) => self.complete_record(record, start),
+            Err(_) => Err(crate::Error::UnterminatedQuote(start)),
+        }
+    }
+
+    /// Check the parsed record with respect to the limits and the field count policy
+    fn complete_record(
+        &mut self,
+        record: &mut ByteRecord,
+        start: Position,
+    ) -> crate::Result<Position> {
+        self.check_limits(record, start)?;
+        self.fit_field_count(record, start)?;
+        Ok(start)
+    }
+
+    /// Check number and size of fields with respect to the limits
+    fn check_limits(&self, record: &ByteRecord, position: Position) -> crate::Result<()> {
+        if let Some(max) = self.config.max_fields
+            && record.len() > max
+        {
+            return Err(crate::Error::LimitExceeded {
+                position,
+                limit: Limit::Fields,
+                max,
+            });
+        }
+        if let Some(max) = self.config.max_field_bytes
+            && record.iter().any(|field| field.len() > max)
+        {
+            return Err(crate::Error::LimitExceeded {
+                position,
+                limit: Limit::FieldBytes,
+                max,
+            });
+        }
+        Ok(())
+    }
+
+    /// Check number of fields with respect to [policy](FieldCount).
+    /// The first record of the stream (headers if any) sets the expected number.
+    fn fit_field_count(
+        &mut self,
+        record: &mut ByteRecord,
+        position: Position,
+    ) -> crate::Result<()> {
+        let expected = *self.field_count.get_or_insert(record.len());
+        let actual = record.len();
+        match self.config.field_count {
+            FieldCount::Flexible => {}
+            FieldCount::Pad if actual < expected => {
+                for _ in actual..expected {
+                    record.push_field(b"");
+                }
+            }
+            FieldCount::Truncate if actual > expected => record.truncate(expected),
+            _ if actual != expected => {
+                return Err(crate::Error::FieldCountMismatch {
+                    position,
+                    expected,
+                    actual,
+                });
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+}
+
+/// Read the next physical line appending at most `max` bytes of it to `line`, lines in `pending` go first
+fn read_line(
+    source: &mut impl BufRead,
+    pending: &mut VecDeque<u8>,
+    line: &mut Vec<u8>,
+    max: usize,
+) -> std::io::Result<usize> {
+    if pending.is_empty() {
+        return source.by_ref().take(max as u64).read_until(b'\n', line);
+    }
+    let (end, _) = line_end(pending.make_contiguous());
+    let end = end.min(max);
+    line.extend(pending.drain(..end));
+    Ok(end)
+}
+
+/// Skip the rest of the physical line advancing `position`
+fn skip_line(
+    source: &mut impl BufRead,
+    pending: &mut VecDeque<u8>,
+    position: &mut Position,
+) -> std::io::Result<()> {
+    loop {
+        let found = if pending.is_empty() {
+            let chunk = source.fill_buf()?;
+            if chunk.is_empty() {
+                return Ok(());
+            }
+            let (len, found) = line_end(chunk);
+            position.advance(&chunk[..len]);
+            source.consume(len);
+            found
+        } else {
+            let chunk = pending.make_contiguous();
+            let (len, found) = line_end(chunk);
+            position.advance(&chunk[..len]);
+            pending.drain(..len);
+            found
+        };
+        if found {
+            return Ok(());
+        }
+    }
+}
+
+/// Offset of the last line in `chunk`, it is 0 if there is a single line
+fn line_start(chunk: &[u8]) -> usize {
+    chunk.iter().rposition(|&b| b == b'\n').map_or(0, |i| i + 1)
+}
+
+/// Length of the first line in `chunk` and whether its end is found
+fn line_end(chunk: &[u8]) -> (usize, bool) {
+    chunk
+        .iter()
+        .position(|&b| b == b'\n')
+        .map_or((chunk.len(), false), |i| (i + 1, true))
+}
+
+/// Errors of the record content which may be handled by the [recovery policy](Recovery)
+fn is_malformed(e: &crate::Error) -> bool {
+    matches!(
+        e.kind(),
+        crate::ErrorKind::UnterminatedQuote
+            | crate::ErrorKind::GarbageAfterQuote
+            | crate::ErrorKind::InvalidUtf8
+            | crate::ErrorKind::FieldCountMismatch
+            | crate::ErrorKind::LimitExceeded
+    )
+}
+
+/// The error leaves the end of the record unknown, so the [resync](Recovery::Resync) reads its lines again.
+/// Records failing the field checks are parsed completely and skipped as a whole.
+fn is_unbounded(e: &crate::Error) -> bool {
+    matches!(
+        e,
+        crate::Error::UnterminatedQuote(_)
+            | crate::Error::GarbageAfterQuote(_)
+            | crate::Error::LimitExceeded {
+                limit: Limit::RecordBytes | Limit::Lines,
+                ..
+            }
+    )
+}
+
+/// The error is caused by a source which has no data yet, e.g. fed by [`CsvPushParser`](crate::CsvPushParser)
+pub(crate) fn would_block(e: &crate::Error) -> bool {
+    matches!(e, crate::Error::ReadFailed { source, .. } if source.kind() == std::io::ErrorKind::WouldBlock)
+}
+
+/// Text of the line without its terminator
+fn line_text(line: &[u8]) -> String {
+    let line = line.strip_suffix(b"\n").unwrap_or(line);
+    let line = line.strip_suffix(b"\r").unwrap_or(line);
+    String::from_utf8_lossy(line).into_owned()
+}
+
+/// Iterator over [records](Record) of [`CsvReader`], see [`CsvReader::records`]
+pub struct Records<'a, R> {
+    reader: &'a mut CsvReader<R>,
+}
+
+impl<R: BufRead> Iterator for Records<'_, R> {
+    type Item = crate::Result<Record>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let mut record = Record::new();
+        match self.reader.read_record(&mut record) {
+            Ok(true) => Some(Ok(record)),
+            Ok(false) => None,
+            Err(e) => Some(Err(e)),
+        }
+    }
+}
+
+/// Iterator over records of [`CsvReader`] deserialized into `T`, see [`CsvReader::deserialize`]
+pub struct DeserializeRecords<'a, R, T> {
+    reader: &'a mut CsvReader<R>,
+    record: Record,
+    _record: PhantomData<T>,
+}
+
+impl<R: BufRead, T: DeserializeOwned> Iterator for DeserializeRecords<'_, R, T> {
+    type Item = crate::Result<T>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        match self.reader.read_record(&mut self.record) {
+            Ok(true) => {}
+            Ok(false) => return None,
+            Err(e) => return Some(Err(e)),
+        }
+        // comments and skipped records are read before the record, so the reader position is not its start
+        let position = self.record.position().unwrap_or_default();
+        let headers = self.reader.headers();
+        Some(de::deserialize_record(&self.record, headers).map_err(|e| {
+            crate::Error::Deserialize {
+                position,
+                field: e.field,
+                header: e
+                    .field
+                    .and_then(|field| headers.and_then(|headers| headers.get(field)))
+                    .cloned(),
+                message: e.message,
+            }
+        }))
+    }
+}
+
+/// Iterator over records of [`CsvReader`] converted by a [`Schema`], see [`CsvReader::typed`]
+pub struct TypedRecords<'a, R> {
+    reader: &'a mut CsvReader<R>,
+    schema: &'a Schema,
+    record: Record,
+}
+
+impl<R: BufRead> Iterator for TypedRecords<'_, R> {
+    type Item = crate::Result<Vec<Value>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        match self.reader.read_record(&mut self.record) {
+            Ok(true) => Some(self.schema.convert(&self.record)),
+            Ok(false) => None,
+            Err(e) => Some(Err(e)),
+        }
     }
 }
 
@@ -70,47 +922,152 @@ impl<R: BufRead> Iterator for CsvReader<R> {
     }
 }
 
-fn parse_row<R: BufRead>(mut source: R, comma: char, dquote: char) -> crate::Result<Box<[String]>> {
-    let mut record_line = String::new();
-    loop {
-        let n = source.read_line(&mut record_line)?;
-        if n == 0 {
-            if record_line.is_empty() {
-                break Err(crate::Error::StreamComplete);
-            } else {
-                // if source exhausted but we have incomplete record parsing fails
-                break Err(crate::Error::UnexpectedEof);
-            }
-        }
-        match parse::record(&record_line, comma, dquote) {
-            Ok((_, fields)) => break Ok(fields.into_boxed_slice()),
-            Err(e) => match e {
-                nom::Err::Incomplete(_) => {
-                    //record in CSV-file may consist of several lines if has escaped fields with newlines inside
-                    continue;
-                }
-                nom::Err::Error(e) | nom::Err::Failure(e) => {
-                    break Err(crate::Error::NomFailed(format!("Nom failed: {}", e)))
+mod config {
+    use crate::SourceEncoding;
+
+    /// Policy for empty and duplicate header names
+    #[derive(Debug, Default, Clone, Copy)]
+    pub enum HeaderNames {
+        /// Keep names as is, lookup by a duplicate name finds the first column
+        #[default]
+        Keep,
+        /// Empty or duplicate name is an error
+        Reject,
+        /// Name empty header as `column_N` where N is a column number starting from 1,
+        /// name duplicate header as `name_N` where N is a number starting from 2
+        Rename,
+        /// Rename empty or duplicate header with a function of its name and column index
+        RenameWith(fn(&str, usize) -> String),
+    }
+
+    /// What the reader does with malformed records: unterminated quotes, garbage after quotes,
+    /// invalid UTF-8 and unexpected number of fields. Malformed headers are always returned as errors.
+    #[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+    pub enum Recovery {
+        /// The error is returned, reading goes on with the next record
+        #[default]
+        Return,
+        /// The error is returned, the stream ends after it
+        Abort,
+        /// The record is skipped and sent to the [quarantine](Config::quarantine) with its error
+        Skip,
+        /// Only the first physical line of the record is sent to the [quarantine](Config::quarantine)
+        /// with the error, reading goes on with the next line. So an unterminated quote does not consume
+        /// the rest of the stream. A line cut by the [record size limit](Config::max_record_bytes)
+        /// is dropped as a whole. Records parsed completely but failing the field count or the field limits
+        /// are skipped as with [`Recovery::Skip`].
+        Resync,
+    }
+
+    /// Resource limit of the reader, see [`Error::LimitExceeded`](crate::Error::LimitExceeded)
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    pub enum Limit {
+        /// Size of a raw record in bytes
+        RecordBytes,
+        /// Size of a field in bytes
+        FieldBytes,
+        /// Number of fields in a record
+        Fields,
+        /// Number of physical lines in a record
+        Lines,
+    }
+
+    impl std::fmt::Display for Limit {
+        fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+            write!(
+                f,
+                "{}",
+                match self {
+                    Limit::RecordBytes => "bytes per record",
+                    Limit::FieldBytes => "bytes per field",
+                    Limit::Fields => "fields per record",
+                    Limit::Lines => "lines per record",
                 }
-            },
+            )
         }
     }
-}
 
-fn load_headers<R: BufRead>(source: R, comma: char, dquote: char) -> Option<Box<[String]>> {
-    parse_row(source, comma, dquote).ok()
-}
+    /// Sink for raw malformed records and their errors
+    pub type Quarantine = Box<dyn FnMut(&[u8], crate::Error) + Send>;
 
-mod config {
+    /// Whitespace trimming around fields.
+    /// Trimmed unquoted fields lose ASCII whitespace at both ends.
+    /// Quoted fields keep whitespace inside the quotes, whitespace outside of them is skipped.
+    #[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+    pub enum Trim {
+        /// Nothing is trimmed as in RFC 4180. Whitespace before an opening quote makes the field unquoted,
+        /// so its quotes are a part of the data. Whitespace after a closing quote is an error.
+        None,
+        /// Whitespace around quotes is skipped, unquoted fields are kept as is in headers and records.
+        /// Fields written by [`CsvWriter`](crate::CsvWriter) are read back intact.
+        #[default]
+        Quotes,
+        /// Only headers are trimmed, records are read as with [`Trim::None`]
+        Headers,
+        /// Records are trimmed, headers are read as with [`Trim::None`]
+        Fields,
+        /// Headers and records are trimmed
+        All,
+    }
+
+    /// Policy for records which have number of fields different from the headers
+    /// (or from the first record if there are no headers). Both the reader and the writer use it.
+    #[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+    pub enum FieldCount {
+        /// Any number of fields is accepted
+        #[default]
+        Flexible,
+        /// Records with different number of fields are errors
+        Strict,
+        /// Missing fields are filled with empty values, extra fields are errors
+        Pad,
+        /// Extra fields are dropped, missing fields are errors
+        Truncate,
+    }
 
     /// Data struct with CSV reader options
     pub struct Config {
         /// User expects CSV headers
         pub has_headers: bool,
-        /// Value separator, default is ',' as in RFC 4180
-        pub separator: char,
-        /// Escape character, default is '"' as in RFC 4180
+        /// Value separator, default is "," as in RFC 4180. It may be any string, e.g. "||" or "\t".
+        /// Empty separator makes every line a single field.
+        pub separator: String,
+        /// Quote character wrapping fields, default is '"' as in RFC 4180
+        pub quote: char,
+        /// Character escaping quotes inside quoted fields. Default is '"', i.e. quotes are doubled
+        /// as in RFC 4180. Any other character, e.g. '\\', makes the next character inside a quoted field
+        /// a part of it as is, so `\"` is a quote and `\\` is a backslash. Escape sequences like `\t`
+        /// are not decoded and unquoted fields are kept as is.
         pub escape: char,
+        /// Whitespace trimming around fields, default is [`Trim::Quotes`]
+        pub trim: Trim,
+        /// Replace invalid UTF-8 sequences with U+FFFD instead of failing
+        pub lossy_utf8: bool,
+        /// Encoding of the source, it is decoded into UTF-8 before parsing.
+        /// Positions of records count bytes of the decoded content.
+        pub encoding: SourceEncoding,
+        /// Policy for records with unexpected number of fields
+        pub field_count: FieldCount,
+        /// Policy for empty and duplicate header names
+        pub header_names: HeaderNames,
+        /// Lines starting with this prefix are comments, they are skipped between records
+        /// and kept for [`CsvReader::comments`](crate::CsvReader::comments). Empty prefix is ignored.
+        pub comment: Option<String>,
+        /// Number of preamble lines at the start of the stream skipped before headers,
+        /// they are kept for [`CsvReader::preamble`](crate::CsvReader::preamble)
+        pub skip_lines: usize,
+        /// What to do with malformed records
+        pub recovery: Recovery,
+        /// Receives raw malformed records skipped with respect to the [recovery policy](Recovery)
+        pub quarantine: Option<Quarantine>,
+        /// Maximum size of a raw record in bytes, longer records are cut and the rest of their line is skipped
+        pub max_record_bytes: Option<usize>,
+        /// Maximum size of a field in bytes
+        pub max_field_bytes: Option<usize>,
+        /// Maximum number of fields in a record
+        pub max_fields: Option<usize>,
+        /// Maximum number of physical lines in a record
+        pub max_lines: Option<usize>,
     }
 
     impl Config {
@@ -120,9 +1077,15 @@ mod config {
             self
         }
 
-        /// Part of a Builder pattern. Sets custom separator value
-        pub fn separator(mut self, sep: char) -> Self {
-            self.separator = sep;
+        /// Part of a Builder pattern. Sets custom separator value, a character or a string
+        pub fn separator(mut self, sep: impl ToString) -> Self {
+            self.separator = sep.to_string();
+            self
+        }
+
+        /// Part of a Builder pattern. Sets custom quote character value
+        pub fn quote(mut self, quote: char) -> Self {
+            self.quote = quote;
             self
         }
 
@@ -131,14 +1094,109 @@ mod config {
             self.escape = esc;
             self
         }
+
+        /// Part of a Builder pattern. Sets whitespace trimming mode
+        pub fn trim(mut self, trim: Trim) -> Self {
+            self.trim = trim;
+            self
+        }
+
+        /// Part of a Builder pattern. Sets encoding of the source
+        pub fn encoding(mut self, encoding: SourceEncoding) -> Self {
+            self.encoding = encoding;
+            self
+        }
+
+        /// Part of a Builder pattern. Sets policy for records with unexpected number of fields
+        pub fn field_count(mut self, policy: FieldCount) -> Self {
+            self.field_count = policy;
+            self
+        }
+
+        /// Part of a Builder pattern. Sets policy for empty and duplicate header names
+        pub fn header_names(mut self, policy: HeaderNames) -> Self {
+            self.header_names = policy;
+            self
+        }
+
+        /// Part of a Builder pattern. Sets prefix of comment lines, e.g. "#". Empty prefix disables comments.
+        pub fn comment(mut self, prefix: impl ToString) -> Self {
+            self.comment = Some(prefix.to_string()).filter(|prefix| !prefix.is_empty());
+            self
+        }
+
+        /// Part of a Builder pattern. Sets number of preamble lines to skip
+        pub fn skip_lines(mut self, lines: usize) -> Self {
+            self.skip_lines = lines;
+            self
+        }
+
+        /// Part of a Builder pattern. Sets recovery policy for malformed records
+        pub fn recovery(mut self, policy: Recovery) -> Self {
+            self.recovery = policy;
+            self
+        }
+
+        /// Part of a Builder pattern. Sets sink for malformed records skipped by the recovery policy
+        pub fn quarantine(
+            mut self,
+            sink: impl FnMut(&[u8], crate::Error) + Send + 'static,
+        ) -> Self {
+            self.quarantine = Some(Box::new(sink));
+            self
+        }
+
+        /// Part of a Builder pattern. Sets maximum size of a raw record in bytes
+        pub fn max_record_bytes(mut self, max: usize) -> Self {
+            self.max_record_bytes = Some(max);
+            self
+        }
+
+        /// Part of a Builder pattern. Sets maximum size of a field in bytes
+        pub fn max_field_bytes(mut self, max: usize) -> Self {
+            self.max_field_bytes = Some(max);
+            self
+        }
+
+        /// Part of a Builder pattern. Sets maximum number of fields in a record
+        pub fn max_fields(mut self, max: usize) -> Self {
+            self.max_fields = Some(max);
+            self
+        }
+
+        /// Part of a Builder pattern. Sets maximum number of physical lines in a record
+        pub fn max_lines(mut self, max: usize) -> Self {
+            self.max_lines = Some(max);
+            self
+        }
+
+        /// Part of a Builder pattern. Sets invalid UTF-8 replacement flag
+        pub fn lossy_utf8(mut self, lossy: bool) -> Self {
+            self.lossy_utf8 = lossy;
+            self
+        }
     }
 
     impl Default for Config {
         fn default() -> Self {
             Self {
                 has_headers: Default::default(),
-                separator: ',',
+                separator: String::from(","),
+                quote: '"',
                 escape: '"',
+                trim: Trim::Quotes,
+                lossy_utf8: false,
+                encoding: SourceEncoding::Utf8,
+                field_count: FieldCount::Flexible,
+                header_names: HeaderNames::Keep,
+                comment: None,
+                skip_lines: 0,
+                recovery: Recovery::Return,
+                quarantine: None,
+                max_record_bytes: None,
+                max_field_bytes: None,
+                max_fields: None,
+                max_lines: None,
             }
         }
     }
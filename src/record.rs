@@ -0,0 +1,368 @@
+use crate::{Headers, Position};
+use std::{fmt::Display, str::FromStr, sync::Arc};
+
+/// CSV record with all of its fields stored in a single buffer.
+/// Fields are accessed by index or by header name if the record has [headers](Headers).
+///
+/// Reuse one record with [`CsvReader::read_record`](crate::CsvReader::read_record)
+/// to read a stream without allocating memory for each record.
+///
+/// # Example
+///
+/// ```
+/// let buf = "name,amount\r\nmilk,1.5".as_bytes();
+/// let config = justcsv::CsvReaderConfig::default().has_headers(true);
+/// let mut reader = justcsv::CsvReader::with_config(buf, config);
+/// let record = reader.records().next().unwrap().unwrap();
+/// assert_eq!(Some("1.5"), record.get("amount"));
+/// assert_eq!("milk", &record["name"]);
+/// assert_eq!(
+///     vec![("name", "milk"), ("amount", "1.5")],
+///     record.pairs().collect::<Vec<_>>()
+/// );
+/// assert_eq!(1.5, record.parse::<f64>("amount").unwrap());
+/// ```
+#[derive(Debug, Default, Clone)]
+pub struct Record {
+    data: String,
+    ends: Vec<usize>,
+    headers: Option<Arc<Headers>>,
+    position: Option<Position>,
+}
+
+impl Record {
+    /// Create an empty record
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Number of fields
+    pub fn len(&self) -> usize {
+        self.ends.len()
+    }
+
+    /// Record has no fields
+    pub fn is_empty(&self) -> bool {
+        self.ends.is_empty()
+    }
+
+    /// Field by its index or by header name
+    pub fn get(&self, index: impl RecordIndex) -> Option<&str> {
+        let index = index.field_index(self)?;
+        let end = *self.ends.get(index)?;
+        let start = index.checked_sub(1).map_or(0, |prev| self.ends[prev]);
+        Some(&self.data[start..end])
+    }
+
+    /// Convert field by its index or by header name with `FromStr`.
+    /// The error carries the header, the raw text of the field and the record position.
+    pub fn parse<T>(&self, index: impl RecordIndex + Display) -> crate::Result<T>
+    where
+        T: FromStr,
+        T::Err: Display,
+    {
+        let column = self.column(&index)?;
+        self[column]
+            .parse()
+            .map_err(|e| self.convert_error(column, None, e))
+    }
+
+    /// Column number of the field by its index or by header name,
+    /// fails if the record has no such field
+    pub(crate) fn column(&self, index: &(impl RecordIndex + Display)) -> crate::Result<usize> {
+        index
+            .field_index(self)
+            .filter(|&column| column < self.len())
+            .ok_or_else(|| crate::Error::MissingField {
+                position: self.position,
+                field: index.to_string(),
+            })
+    }
+
+    /// Error of converting the field in `column`, `header` defaults to the record headers
+    pub(crate) fn convert_error(
+        &self,
+        column: usize,
+        header: Option<&str>,
+        message: impl Display,
+    ) -> crate::Error {
+        crate::Error::Convert {
+            position: self.position,
+            column,
+            header: header
+                .or_else(|| self.headers()?.names().get(column).map(String::as_str))
+                .map(str::to_owned),
+            text: self[column].to_owned(),
+            message: message.to_string(),
+        }
+    }
+
+    /// Iterate through fields
+    pub fn iter(&self) -> Fields<'_> {
+        Fields {
+            record: self,
+            index: 0,
+        }
+    }
+
+    /// Iterate through pairs of header name and field value.
+    /// Yields nothing if the record has no headers.
+    pub fn pairs(&self) -> impl Iterator<Item = (&str, &str)> {
+        self.headers
+            .iter()
+            .flat_map(|headers| headers.names())
+            .map(String::as_str)
+            .zip(self.iter())
+    }
+
+    /// Headers of the record
+    pub fn headers(&self) -> Option<&Headers> {
+        self.headers.as_deref()
+    }
+
+    /// Attach headers to the record
+    pub fn set_headers(&mut self, headers: Option<Arc<Headers>>) {
+        self.headers = headers;
+    }
+
+    /// Start of the record in the stream if it is read by a reader
+    pub fn position(&self) -> Option<Position> {
+        self.position
+    }
+
+    pub(crate) fn set_position(&mut self, position: Position) {
+        self.position = Some(position);
+    }
+
+    /// Attach headers unless they are attached already
+    pub(crate) fn share_headers(&mut self, headers: Option<&Arc<Headers>>) {
+        match (self.headers.as_ref(), headers) {
+            (Some(attached), Some(headers)) if Arc::ptr_eq(attached, headers) => {}
+            _ => self.headers = headers.cloned(),
+        }
+    }
+
+    /// Remove all fields keeping allocated memory
+    pub fn clear(&mut self) {
+        self.data.clear();
+        self.ends.clear();
+    }
+
+    /// Append a field
+    pub fn push_field(&mut self, field: &str) {
+        self.data.push_str(field);
+        self.ends.push(self.data.len());
+    }
+
+    /// Copy fields into a boxed slice as [`CsvReader`](crate::CsvReader) iterator yields them
+    pub fn to_boxed_slice(&self) -> Box<[String]> {
+        self.iter().map(str::to_owned).collect()
+    }
+}
+
+/// Type to index fields of a [`Record`]: column number or header name
+pub trait RecordIndex {
+    /// Column number of the field in `record`
+    fn field_index(&self, record: &Record) -> Option<usize>;
+}
+
+impl RecordIndex for usize {
+    fn field_index(&self, _record: &Record) -> Option<usize> {
+        Some(*self)
+    }
+}
+
+impl RecordIndex for &str {
+    fn field_index(&self, record: &Record) -> Option<usize> {
+        record.headers()?.index_of(self)
+    }
+}
+
+impl<I: RecordIndex> std::ops::Index<I> for Record {
+    type Output = str;
+
+    fn index(&self, index: I) -> &str {
+        self.get(index).expect("no such field in the record")
+    }
+}
+
+/// Records are equal if they have the same fields and headers, their positions are not compared
+impl PartialEq for Record {
+    fn eq(&self, other: &Self) -> bool {
+        self.data == other.data && self.ends == other.ends && self.headers == other.headers
+    }
+}
+
+impl Eq for Record {}
+
+impl<'a> IntoIterator for &'a Record {
+    type Item = &'a str;
+    type IntoIter = Fields<'a>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<F: AsRef<str>> FromIterator<F> for Record {
+    fn from_iter<T: IntoIterator<Item = F>>(iter: T) -> Self {
+        let mut record = Self::new();
+        for field in iter {
+            record.push_field(field.as_ref());
+        }
+        record
+    }
+}
+
+/// Iterator through fields of a [`Record`]
+pub struct Fields<'a> {
+    record: &'a Record,
+    index: usize,
+}
+
+impl<'a> Iterator for Fields<'a> {
+    type Item = &'a str;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let field = self.record.get(self.index)?;
+        self.index += 1;
+        Some(field)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let rest = self.record.len() - self.index;
+        (rest, Some(rest))
+    }
+}
+
+impl ExactSizeIterator for Fields<'_> {}
+
+/// CSV record with fields as raw bytes, they are not required to be UTF-8.
+///
+/// Reuse one record with [`CsvReader::read_byte_record`](crate::CsvReader::read_byte_record)
+/// to read a stream in legacy encodings or with broken UTF-8.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct ByteRecord {
+    data: Vec<u8>,
+    ends: Vec<usize>,
+}
+
+impl ByteRecord {
+    /// Create an empty record
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Number of fields
+    pub fn len(&self) -> usize {
+        self.ends.len()
+    }
+
+    /// Record has no fields
+    pub fn is_empty(&self) -> bool {
+        self.ends.is_empty()
+    }
+
+    /// Field by its index
+    pub fn get(&self, index: usize) -> Option<&[u8]> {
+        let end = *self.ends.get(index)?;
+        let start = index.checked_sub(1).map_or(0, |prev| self.ends[prev]);
+        Some(&self.data[start..end])
+    }
+
+    /// Iterate through fields
+    pub fn iter(&self) -> ByteFields<'_> {
+        ByteFields {
+            record: self,
+            index: 0,
+        }
+    }
+
+    /// Remove all fields keeping allocated memory
+    pub fn clear(&mut self) {
+        self.data.clear();
+        self.ends.clear();
+    }
+
+    /// Append a field
+    pub fn push_field(&mut self, field: &[u8]) {
+        self.data.extend_from_slice(field);
+        self.ends.push(self.data.len());
+    }
+
+    /// Keep first `len` fields dropping the rest
+    pub fn truncate(&mut self, len: usize) {
+        if len < self.len() {
+            self.ends.truncate(len);
+            self.data
+                .truncate(self.ends.last().copied().unwrap_or_default());
+        }
+    }
+
+    /// Convert fields into `record` replacing invalid UTF-8 sequences with U+FFFD
+    pub fn to_record_lossy(&self, record: &mut Record) {
+        record.clear();
+        for field in self {
+            record.push_field(&String::from_utf8_lossy(field));
+        }
+    }
+
+    /// Convert fields into `record`. Fails with index of the first field which is not UTF-8
+    pub fn to_record(&self, record: &mut Record) -> Result<(), usize> {
+        record.clear();
+        for (i, field) in self.iter().enumerate() {
+            record.push_field(std::str::from_utf8(field).map_err(|_| i)?);
+        }
+        Ok(())
+    }
+}
+
+impl std::ops::Index<usize> for ByteRecord {
+    type Output = [u8];
+
+    fn index(&self, index: usize) -> &[u8] {
+        self.get(index).expect("field index is out of bounds")
+    }
+}
+
+impl<'a> IntoIterator for &'a ByteRecord {
+    type Item = &'a [u8];
+    type IntoIter = ByteFields<'a>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<F: AsRef<[u8]>> FromIterator<F> for ByteRecord {
+    fn from_iter<T: IntoIterator<Item = F>>(iter: T) -> Self {
+        let mut record = Self::new();
+        for field in iter {
+            record.push_field(field.as_ref());
+        }
+        record
+    }
+}
+
+/// Iterator through fields of a [`ByteRecord`]
+pub struct ByteFields<'a> {
+    record: &'a ByteRecord,
+    index: usize,
+}
+
+impl<'a> Iterator for ByteFields<'a> {
+    type Item = &'a [u8];
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let field = self.record.get(self.index)?;
+        self.index += 1;
+        Some(field)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let rest = self.record.len() - self.index;
+        (rest, Some(rest))
+    }
+}
+
+impl ExactSizeIterator for ByteFields<'_> {}
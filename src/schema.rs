@@ -0,0 +1,175 @@
+use crate::Record;
+use std::sync::Arc;
+
+/// Typed value of a field converted by a [`Schema`]
+#[derive(Debug, Clone, PartialEq)]
+pub enum Value {
+    /// Empty field of an [optional](Schema::optional) column
+    Null,
+    /// Text as is
+    Str(String),
+    /// Integer number
+    Int(i64),
+    /// Floating point number
+    Float(f64),
+    /// `true` or `false`
+    Bool(bool),
+}
+
+/// Function converting raw text of a field, the error message is reported in [`Error::Convert`](crate::Error::Convert)
+pub type Converter = Arc<dyn Fn(&str) -> Result<Value, String> + Send + Sync>;
+
+/// Type of a [`Schema`] column
+#[derive(Clone)]
+pub enum ColumnType {
+    /// [`Value::Str`], any text is valid
+    Str,
+    /// [`Value::Int`] parsed by `i64::from_str`
+    Int,
+    /// [`Value::Float`] parsed by `f64::from_str`
+    Float,
+    /// [`Value::Bool`] parsed by `bool::from_str`
+    Bool,
+    /// Value produced by the custom converter
+    Custom(Converter),
+}
+
+impl ColumnType {
+    /// Convert raw text of a field
+    pub fn convert(&self, text: &str) -> Result<Value, String> {
+        match self {
+            Self::Str => Ok(Value::Str(text.to_owned())),
+            Self::Int => text.parse().map(Value::Int).map_err(|e| e.to_string()),
+            Self::Float => text.parse().map(Value::Float).map_err(|e| e.to_string()),
+            Self::Bool => text.parse().map(Value::Bool).map_err(|e| e.to_string()),
+            Self::Custom(converter) => converter(text),
+        }
+    }
+}
+
+impl std::fmt::Debug for ColumnType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Str => write!(f, "Str"),
+            Self::Int => write!(f, "Int"),
+            Self::Float => write!(f, "Float"),
+            Self::Bool => write!(f, "Bool"),
+            Self::Custom(_) => write!(f, "Custom"),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+struct Column {
+    name: String,
+    column_type: ColumnType,
+    optional: bool,
+}
+
+/// Types of columns declared up front. Records are converted into values in the order of the declaration.
+/// Columns are found by name if records have [headers](crate::Headers), otherwise the declaration order
+/// is the order of fields.
+///
+/// # Example
+///
+/// ```
+/// use justcsv::{ColumnType, Schema, Value};
+///
+/// let buf = "name,amount,discount,unit\r\nmilk,1.5,,l\r\nsugar,2,10,KG".as_bytes();
+/// let config = justcsv::CsvReaderConfig::default().has_headers(true);
+/// let mut reader = justcsv::CsvReader::with_config(buf, config);
+/// let schema = Schema::new()
+///     .column("name", ColumnType::Str)
+///     .column("amount", ColumnType::Float)
+///     .optional("discount", ColumnType::Int)
+///     .custom("unit", |text| Ok(Value::Str(text.to_lowercase())));
+/// let rows = reader.typed(&schema).collect::<justcsv::Result<Vec<_>>>().unwrap();
+/// assert_eq!(
+///     vec![
+///         Value::Str("sugar".to_owned()),
+///         Value::Float(2.0),
+///         Value::Int(10),
+///         Value::Str("kg".to_owned()),
+///     ],
+///     rows[1]
+/// );
+/// assert_eq!(Value::Null, rows[0][2]);
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct Schema {
+    columns: Vec<Column>,
+}
+
+impl Schema {
+    /// Create schema without columns
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Part of a Builder pattern. Declare the next column, its fields must be converted
+    pub fn column(self, name: impl ToString, column_type: ColumnType) -> Self {
+        self.declare(name, column_type, false)
+    }
+
+    /// Part of a Builder pattern. Declare the next column, its empty fields are [`Value::Null`]
+    pub fn optional(self, name: impl ToString, column_type: ColumnType) -> Self {
+        self.declare(name, column_type, true)
+    }
+
+    /// Part of a Builder pattern. Declare the next column converted by `converter`
+    pub fn custom(
+        self,
+        name: impl ToString,
+        converter: impl Fn(&str) -> Result<Value, String> + Send + Sync + 'static,
+    ) -> Self {
+        self.column(name, ColumnType::Custom(Arc::new(converter)))
+    }
+
+    fn declare(mut self, name: impl ToString, column_type: ColumnType, optional: bool) -> Self {
+        self.columns.push(Column {
+            name: name.to_string(),
+            column_type,
+            optional,
+        });
+        self
+    }
+
+    /// Names of the declared columns
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.columns.iter().map(|column| column.name.as_str())
+    }
+
+    /// Number of the declared columns
+    pub fn len(&self) -> usize {
+        self.columns.len()
+    }
+
+    /// Schema has no columns
+    pub fn is_empty(&self) -> bool {
+        self.columns.is_empty()
+    }
+
+    /// Validate `record` and convert its fields into values of the declared columns.
+    /// Fails with [`Error::MissingField`](crate::Error::MissingField) or
+    /// [`Error::Convert`](crate::Error::Convert) for the first failed column.
+    pub fn convert(&self, record: &Record) -> crate::Result<Vec<Value>> {
+        self.columns
+            .iter()
+            .enumerate()
+            .map(|(i, column)| {
+                let index = match record.headers() {
+                    Some(_) => record.column(&column.name.as_str())?,
+                    None => record.column(&i)?,
+                };
+                let text = &record[index];
+                if column.optional && text.is_empty() {
+                    return Ok(Value::Null);
+                }
+                column
+                    .column_type
+                    .convert(text)
+                    .map_err(|message| record.convert_error(index, Some(&column.name), message))
+            })
+            .collect()
+    }
+}
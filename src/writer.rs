@@ -1,27 +1,51 @@
-pub use config::{Config as CsvWriterConfig, NewLine};
+pub use config::{Config as CsvWriterConfig, NewLine, OnDrop, QuoteStyle, Sanitize, Terminator};
 
-use std::io::Write;
+use crate::{FieldCount, encoding::Encoder, ser};
+use serde::Serialize;
+use std::{
+    borrow::Cow,
+    fmt::{Display, Write as _},
+    io::Write,
+};
 
 /// CSV writer to save UTF-8 content as comma separated values.
 /// Basically CSV document is a slice of records which are basically `&str` slices.
+/// Output may be converted into another [encoding](CsvWriterConfig::encoding).
+///
+/// Output is buffered, so it reaches the destination after [`CsvWriter::flush`] or [`CsvWriter::into_inner`].
+/// The writer dropped without them flushes the buffer with respect to [config](CsvWriterConfig::on_drop).
 ///
 /// # Example
 ///
 /// ```
-/// let mut buf = Vec::new();
 /// let row = vec!["1", "2", "3"];
-/// let mut writer = justcsv::CsvWriter::new(&mut buf);
+/// let mut writer = justcsv::CsvWriter::new(Vec::new());
 /// writer.write_row(&row).unwrap();
 /// writer.write_row(["4", "5\"abc\"X", "6\n\tagain"]).unwrap();
 /// assert_eq!(
 ///     b"1,2,3\r\n4,\"5\"\"abc\"\"X\",\"6\n\tagain\"",
-///     buf.as_slice()
+///     writer.into_inner().unwrap().as_slice()
 /// );
 /// ```
-pub struct CsvWriter<W> {
-    dest: W,
+pub struct CsvWriter<W: Write> {
+    /// It is taken only by `into_inner`
+    dest: Option<W>,
     config: CsvWriterConfig,
-    is_dirty: bool,
+    /// Number of rows written including headers
+    rows: usize,
+    /// Number of fields in headers or in the first row
+    width: Option<usize>,
+    encoder: Encoder,
+    /// Encoded output which is not written into `dest` yet
+    buffer: Vec<u8>,
+    /// Escaped fields of the record being written, it is encoded when the record ends
+    record: String,
+    /// Number of fields passed for the record being written
+    fields: usize,
+    /// A field of the record being written failed, the rest of it is ignored until it ends
+    poisoned: bool,
+    /// Field formatted before escaping
+    field: String,
 }
 
 impl<W: Write> CsvWriter<W> {
@@ -32,38 +56,147 @@ impl<W: Write> CsvWriter<W> {
 
     /// Creates a CSV writer with options passed as [config](CsvWriterConfig)
     pub fn with_config(dest: W, config: CsvWriterConfig) -> Self {
+        let encoder = Encoder::new(config.encoding, config.bom);
         Self {
-            dest,
+            dest: Some(dest),
+            buffer: Vec::with_capacity(config.buffer_capacity),
             config,
-            is_dirty: false,
+            rows: 0,
+            width: None,
+            encoder,
+            record: String::new(),
+            fields: 0,
+            poisoned: false,
+            field: String::new(),
         }
     }
 
-    /// Save next row of comma separated values
+    /// Save next row of comma separated values.
+    /// Its number of fields is checked against the headers with respect to [config](CsvWriterConfig::field_count).
     pub fn write_row<Field: AsRef<str>>(&mut self, row: impl AsRef<[Field]>) -> crate::Result<()> {
-        if self.is_dirty {
-            write!(self.dest, "{}", self.config.newline)?;
-        } else {
-            self.is_dirty = true;
+        for field in row.as_ref() {
+            if let Err(e) = self.push_field(field.as_ref()) {
+                self.discard_record();
+                return Err(e);
+            }
+        }
+        self.end_record()
+    }
+
+    /// Save next row of fields of any `Display` type, e.g. numbers, without converting them into strings first
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// let mut writer = justcsv::CsvWriter::new(Vec::new());
+    /// writer.write_fields([1.5, -2.0]).unwrap();
+    /// writer.write_fields([&"milk" as &dyn std::fmt::Display, &2, &'l']).unwrap();
+    /// assert_eq!(b"1.5,-2\r\nmilk,2,l", writer.into_inner().unwrap().as_slice());
+    /// ```
+    pub fn write_fields(
+        &mut self,
+        fields: impl IntoIterator<Item = impl Display>,
+    ) -> crate::Result<()> {
+        for field in fields {
+            if let Err(e) = self.write_field(field) {
+                self.discard_record();
+                return Err(e);
+            }
+        }
+        self.end_record()
+    }
+
+    /// Append a field of any `Display` type to the current record, [`CsvWriter::end_record`] completes it.
+    /// If the field fails the whole record is discarded: the next fields are ignored
+    /// and [`CsvWriter::end_record`] fails without writing anything.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// let mut writer = justcsv::CsvWriter::new(Vec::new());
+    /// for x in [1, 2] {
+    ///     writer.write_field(x).unwrap();
+    ///     writer.write_field(f64::from(x).sqrt()).unwrap();
+    ///     writer.write_field("a, b").unwrap();
+    ///     writer.end_record().unwrap();
+    /// }
+    /// assert_eq!(
+    ///     "1,1,\"a, b\"\r\n2,1.4142135623730951,\"a, b\"",
+    ///     String::from_utf8(writer.into_inner().unwrap()).unwrap()
+    /// );
+    /// ```
+    pub fn write_field(&mut self, field: impl Display) -> crate::Result<()> {
+        if self.poisoned {
+            return Ok(());
+        }
+        let mut formatted = std::mem::take(&mut self.field);
+        formatted.clear();
+        let result = match write!(formatted, "{field}") {
+            Ok(()) => self.push_field(&formatted),
+            Err(_) => {
+                self.poison_record();
+                Err(crate::Error::custom("field cannot be formatted"))
+            }
+        };
+        self.field = formatted;
+        result
+    }
+
+    /// Complete the record started by [`CsvWriter::write_field`].
+    /// Its number of fields is checked against the headers with respect to [config](CsvWriterConfig::field_count).
+    /// Fails if any field of the record failed, the next field starts a new record then.
+    pub fn end_record(&mut self) -> crate::Result<()> {
+        if self.poisoned {
+            self.discard_record();
+            return Err(crate::Error::custom(
+                "record is discarded after a failed field",
+            ));
+        }
+        let result = self.encode_record();
+        self.discard_record();
+        result?;
+        if self.buffer.len() >= self.config.buffer_capacity {
+            self.write_buffer()?;
         }
-        let output = row
-            .as_ref()
-            .iter()
-            .map(|field| self.escape_if_needed(field.as_ref()))
-            .collect::<Vec<_>>()
-            .join(self.config.separator.as_str());
-        write!(self.dest, "{}", output)?;
         Ok(())
     }
 
     /// Save CSV headers. Basically the same as `write_row` but returns error if headers are saved after any records
     pub fn write_headers(&mut self, headers: &[impl AsRef<str>]) -> crate::Result<()> {
-        if self.is_dirty {
+        if self.rows > 0 || self.fields > 0 {
             return Err(crate::Error::WriteHeadersAfterRecords);
         }
         self.write_row(headers)
     }
 
+    /// Save any `serde::Serialize` value as a record.
+    /// Structs and maps are saved with headers made of their field names
+    /// if nothing was written into the stream before.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// #[derive(serde::Serialize)]
+    /// struct Row {
+    ///     name: &'static str,
+    ///     amount: Option<f64>,
+    /// }
+    ///
+    /// let mut writer = justcsv::CsvWriter::new(Vec::new());
+    /// writer.serialize(&Row { name: "milk", amount: Some(1.5) }).unwrap();
+    /// writer.serialize(&Row { name: "bread", amount: None }).unwrap();
+    /// let buf = writer.into_inner().unwrap();
+    /// assert_eq!(b"name,amount\r\nmilk,1.5\r\nbread,", buf.as_slice());
+    /// ```
+    pub fn serialize<T: Serialize + ?Sized>(&mut self, record: &T) -> crate::Result<()> {
+        let (headers, fields) = ser::serialize_record(record)
+            .map_err(|ser::SerError(message)| crate::Error::Serialize(message))?;
+        if let Some(headers) = headers.filter(|_| self.rows == 0) {
+            self.write_headers(&headers)?;
+        }
+        self.write_row(fields)
+    }
+
     /// Save whole CSV document
     pub fn write_document<Field: AsRef<str>, Record: AsRef<[Field]>>(
         &mut self,
@@ -75,25 +208,240 @@ impl<W: Write> CsvWriter<W> {
         Ok(())
     }
 
-    fn escape_if_needed(&self, field: &str) -> String {
-        if field
-            .chars()
-            .any(|c| c < ' ' || self.config.separator.contains(c) || c == self.config.escape)
+    /// Escape the field appending it to the current record
+    fn push_field(&mut self, field: &str) -> crate::Result<()> {
+        if self.poisoned {
+            return Ok(());
+        }
+        let column = self.fields;
+        self.fields += 1;
+        // extra fields are dropped on the fly
+        if self.config.field_count == FieldCount::Truncate
+            && self.width.is_some_and(|width| column >= width)
         {
-            format!(
-                "\"{}\"",
-                field.replace(
-                    self.config.escape.to_string().as_str(),
-                    format!("{}{}", self.config.escape, self.config.escape).as_str(),
-                )
-            )
-        } else {
-            field.to_owned()
+            return Ok(());
+        }
+        if column > 0 {
+            self.record.push_str(&self.config.separator);
+        }
+        let result = escape_field(&self.config, column, field, &mut self.record);
+        if result.is_err() {
+            self.poison_record();
+        }
+        result
+    }
+
+    /// Check number of fields of the current record and encode it into the buffer with the new line
+    fn encode_record(&mut self) -> crate::Result<()> {
+        let len = self.fit_row_length(self.fields)?;
+        for column in self.fields..len {
+            if column > 0 {
+                self.record.push_str(&self.config.separator);
+            }
+            escape_field(&self.config, column, "", &mut self.record)?;
+        }
+        let newline = self.config.newline.to_string();
+        match self.config.terminator {
+            Terminator::Between if self.rows > 0 => self.record.insert_str(0, &newline),
+            Terminator::Between => {}
+            Terminator::AfterEach => self.record.push_str(&newline),
+        }
+        let buffered = self.buffer.len();
+        if let Err(e) = self.encoder.encode(&self.record, &mut self.buffer) {
+            self.buffer.truncate(buffered);
+            return Err(e);
+        }
+        self.width.get_or_insert(len);
+        self.rows += 1;
+        Ok(())
+    }
+
+    fn discard_record(&mut self) {
+        self.record.clear();
+        self.fields = 0;
+        self.poisoned = false;
+    }
+
+    /// Drop the fields of the failed record and ignore the rest of it until it ends
+    fn poison_record(&mut self) {
+        self.discard_record();
+        self.poisoned = true;
+    }
+
+    /// Number of fields to write for a row of `actual` fields with respect to the [policy](FieldCount).
+    /// Headers or the first row set the expected number.
+    fn fit_row_length(&self, actual: usize) -> crate::Result<usize> {
+        let Some(expected) = self.width else {
+            return Ok(actual);
+        };
+        match self.config.field_count {
+            FieldCount::Flexible => Ok(actual),
+            FieldCount::Pad if actual < expected => Ok(expected),
+            FieldCount::Truncate if actual > expected => Ok(expected),
+            _ if actual != expected => Err(crate::Error::RowLengthMismatch {
+                row: self.rows,
+                expected,
+                actual,
+            }),
+            _ => Ok(actual),
         }
     }
+
+    /// Write buffered output and flush the destination
+    pub fn flush(&mut self) -> crate::Result<()> {
+        self.write_buffer()?;
+        Ok(self.dest().flush()?)
+    }
+
+    /// Flush the writer and unwrap the destination
+    pub fn into_inner(mut self) -> crate::Result<W> {
+        self.flush()?;
+        Ok(self
+            .dest
+            .take()
+            .expect("destination is present until into_inner"))
+    }
+
+    /// Reference to the destination, buffered output is not written into it yet
+    pub fn get_ref(&self) -> &W {
+        self.dest
+            .as_ref()
+            .expect("destination is present until into_inner")
+    }
+
+    /// Mutable reference to the destination. Buffered output is not written into it yet,
+    /// so [flush](CsvWriter::flush) the writer before writing into the destination directly.
+    pub fn get_mut(&mut self) -> &mut W {
+        self.dest()
+    }
+
+    fn dest(&mut self) -> &mut W {
+        self.dest
+            .as_mut()
+            .expect("destination is present until into_inner")
+    }
+
+    /// Move buffered output into the destination, it is dropped if the destination fails
+    fn write_buffer(&mut self) -> crate::Result<()> {
+        if self.buffer.is_empty() {
+            return Ok(());
+        }
+        let dest = self
+            .dest
+            .as_mut()
+            .expect("destination is present until into_inner");
+        let result = dest.write_all(&self.buffer);
+        self.buffer.clear();
+        Ok(result?)
+    }
+}
+
+impl<W: Write> Drop for CsvWriter<W> {
+    fn drop(&mut self) {
+        if self.dest.is_none() {
+            return;
+        }
+        if let Err(e) = self.write_buffer() {
+            self.report_drop(e);
+        }
+    }
+}
+
+impl<W: Write> CsvWriter<W> {
+    /// Number of bytes of output which are not written into the destination yet
+    #[cfg(feature = "tokio")]
+    pub(crate) fn buffered(&self) -> usize {
+        self.buffer.len()
+    }
+
+    /// Handle the error of flushing on drop with respect to the [policy](OnDrop)
+    pub(crate) fn report_drop(&mut self, e: crate::Error) {
+        match &mut self.config.on_drop {
+            OnDrop::Ignore => {}
+            OnDrop::Stderr => eprintln!("CSV writer failed to flush on drop: {e}"),
+            // do not abort the process if the writer is dropped by another panic
+            OnDrop::Panic if std::thread::panicking() => {}
+            OnDrop::Panic => panic!("CSV writer failed to flush on drop: {e}"),
+            OnDrop::Report(sink) => sink(e),
+        }
+    }
+}
+
+/// Escape the field appending it to `out` with respect to quote style and sanitizing
+fn escape_field(
+    config: &CsvWriterConfig,
+    column: usize,
+    field: &str,
+    out: &mut String,
+) -> crate::Result<()> {
+    let field = sanitize(config, column, field)?;
+    let field = field.as_ref();
+    let necessary = field.chars().any(|c| {
+        c < ' ' || config.separator.contains(c) || c == config.quote || c == config.escape
+    });
+    let quote = match config.quote_style_of(column) {
+        QuoteStyle::Necessary => necessary,
+        QuoteStyle::Always => true,
+        QuoteStyle::NonNumeric => necessary || !is_numeric(field),
+        QuoteStyle::Never if necessary => {
+            return Err(crate::Error::QuoteRequired {
+                column,
+                field: field.to_owned(),
+            });
+        }
+        QuoteStyle::Never => false,
+    };
+    if !quote {
+        out.push_str(field);
+        return Ok(());
+    }
+    out.push(config.quote);
+    for c in field.chars() {
+        if c == config.quote || c == config.escape {
+            out.push(config.escape);
+        }
+        out.push(c);
+    }
+    out.push(config.quote);
+    Ok(())
+}
+
+/// Neutralize the field which spreadsheets may take for a formula with respect to [config](CsvWriterConfig::sanitize)
+fn sanitize<'a>(
+    config: &CsvWriterConfig,
+    column: usize,
+    field: &'a str,
+) -> crate::Result<Cow<'a, str>> {
+    let formula = field.starts_with(['=', '+', '-', '@', '\t', '\r'])
+        && !(config.allow_numbers.contains(&column) && is_numeric(field));
+    match config.sanitize {
+        Sanitize::Prefix if formula => Ok(Cow::Owned(format!("'{field}"))),
+        Sanitize::Reject if formula => Err(crate::Error::FormulaInjection {
+            column,
+            field: field.to_owned(),
+        }),
+        _ => Ok(Cow::Borrowed(field)),
+    }
+}
+
+/// Field looks like a decimal number: optional sign, digits with optional fraction and exponent
+fn is_numeric(field: &str) -> bool {
+    let unsigned = field.strip_prefix(['+', '-']).unwrap_or(field);
+    let (mantissa, exponent) = unsigned.split_once(['e', 'E']).unwrap_or((unsigned, "0"));
+    let exponent = exponent.strip_prefix(['+', '-']).unwrap_or(exponent);
+    let (integer, fraction) = mantissa.split_once('.').unwrap_or((mantissa, ""));
+    (!integer.is_empty() || !fraction.is_empty())
+        && !exponent.is_empty()
+        && [integer, fraction, exponent]
+            .iter()
+            .all(|part| part.bytes().all(|b| b.is_ascii_digit()))
 }
 
 mod config {
+    use crate::FieldCount;
+    use encoding_rs::Encoding;
+    use std::collections::{HashMap, HashSet};
+
     /// New line type
     pub enum NewLine {
         /// According RFC 4180 `CRLF`
@@ -118,14 +466,94 @@ mod config {
         }
     }
 
+    /// Where the [new line](NewLine) goes
+    #[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+    pub enum Terminator {
+        /// Between rows, so the output does not end with a new line
+        #[default]
+        Between,
+        /// After every row including the last one as POSIX tools expect
+        AfterEach,
+    }
+
+    /// When fields are wrapped in quotes
+    #[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+    pub enum QuoteStyle {
+        /// Only fields with separators, quotes, escape or control characters
+        #[default]
+        Necessary,
+        /// Every field including empty ones
+        Always,
+        /// Every field which is not a number
+        NonNumeric,
+        /// No field, the ones which need quotes fail with [`Error::QuoteRequired`](crate::Error::QuoteRequired)
+        Never,
+    }
+
+    /// Protection against formula injection: spreadsheets may execute fields starting with
+    /// `=`, `+`, `-`, `@`, tab or carriage return
+    #[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+    pub enum Sanitize {
+        /// Fields are written as is
+        #[default]
+        Off,
+        /// Such fields are prefixed with `'` so spreadsheets show them as text
+        Prefix,
+        /// Such fields fail with [`Error::FormulaInjection`](crate::Error::FormulaInjection)
+        Reject,
+    }
+
+    /// What the writer does if writing buffered output fails when it is dropped
+    /// without [`CsvWriter::flush`](crate::CsvWriter::flush) or [`CsvWriter::into_inner`](crate::CsvWriter::into_inner)
+    #[derive(Default)]
+    pub enum OnDrop {
+        /// The error is ignored and the output is lost as with `std::io::BufWriter`
+        Ignore,
+        /// The error is printed to the standard error
+        #[default]
+        Stderr,
+        /// The writer panics unless it is dropped while panicking already
+        Panic,
+        /// The error is passed to the function
+        Report(Box<dyn FnMut(crate::Error) + Send>),
+    }
+
     /// Data struct with CSV writer options
     pub struct Config {
         /// Value separator, default is ','
         pub separator: String,
-        /// Escape character, default is '"'
+        /// Quote character wrapping fields, default is '"'
+        pub quote: char,
+        /// Character escaping quotes inside quoted fields. Default is '"', i.e. quotes are doubled
+        /// as in RFC 4180. Any other character, e.g. '\\', is put before quotes and itself,
+        /// so fields containing it are quoted.
         pub escape: char,
         /// New line type
         pub newline: NewLine,
+        /// Whether the new line separates rows or terminates each of them, default is [`Terminator::Between`]
+        pub terminator: Terminator,
+        /// Encoding of the output, default is UTF-8
+        pub encoding: &'static Encoding,
+        /// Start the output with a byte order mark, it helps Excel to detect UTF-8 and UTF-16
+        pub bom: bool,
+        /// When fields are quoted, default is [`QuoteStyle::Necessary`]
+        pub quote_style: QuoteStyle,
+        /// Quote styles of particular columns overriding `quote_style`, keys are column indices
+        pub column_quote_styles: HashMap<usize, QuoteStyle>,
+        /// Policy for rows with number of fields different from the headers (or from the first row
+        /// if there are no headers), default is [`FieldCount::Flexible`].
+        /// [`FieldCount::Strict`] rejects them with [`Error::RowLengthMismatch`](crate::Error::RowLengthMismatch),
+        /// [`FieldCount::Pad`] fills missing fields with empty values.
+        pub field_count: FieldCount,
+        /// Protection against formula injection, default is [`Sanitize::Off`]
+        pub sanitize: Sanitize,
+        /// Columns where numbers like `-1.5` or `+7` are left untouched by [`Sanitize`], keys are column indices
+        pub allow_numbers: HashSet<usize>,
+        /// Size of the output buffer, it is written into the destination when it is full.
+        /// Default is 8 KiB, 0 writes every row at once.
+        pub buffer_capacity: usize,
+        /// What to do with an error of writing buffered output on drop, default is [`OnDrop::Stderr`]
+        pub on_drop: OnDrop,
     }
 
     impl Config {
@@ -134,15 +562,21 @@ mod config {
             Default::default()
         }
 
-        /// Part of Builder pattern. Sets custom value separator
-        pub fn separator(mut self, comma: char) -> Self {
+        /// Part of Builder pattern. Sets custom value separator, a character or a string
+        pub fn separator(mut self, comma: impl ToString) -> Self {
             self.separator = comma.to_string();
             self
         }
 
+        /// Part of Builder pattern. Sets custom quote character instead of '"'
+        pub fn quote(mut self, quote: char) -> Self {
+            self.quote = quote;
+            self
+        }
+
         /// Part of Builder pattern. Sets custom escape character instead of '"'
-        pub fn escape(mut self, dquote: char) -> Self {
-            self.escape = dquote;
+        pub fn escape(mut self, escape: char) -> Self {
+            self.escape = escape;
             self
         }
 
@@ -163,14 +597,98 @@ mod config {
             self.newline = NewLine::Custom(eoln.to_string());
             self
         }
+
+        /// Part of Builder pattern. Sets where the new line goes
+        pub fn terminator(mut self, terminator: Terminator) -> Self {
+            self.terminator = terminator;
+            self
+        }
+
+        /// Part of Builder pattern. Sets encoding of the output
+        pub fn encoding(mut self, encoding: &'static Encoding) -> Self {
+            self.encoding = encoding;
+            self
+        }
+
+        /// Part of Builder pattern. Sets byte order mark flag
+        pub fn bom(mut self, bom: bool) -> Self {
+            self.bom = bom;
+            self
+        }
+
+        /// Part of Builder pattern. Sets quote style of all the columns
+        pub fn quote_style(mut self, style: QuoteStyle) -> Self {
+            self.quote_style = style;
+            self
+        }
+
+        /// Part of Builder pattern. Sets quote style of the column with index `column`
+        pub fn column_quote_style(mut self, column: usize, style: QuoteStyle) -> Self {
+            self.column_quote_styles.insert(column, style);
+            self
+        }
+
+        /// Part of Builder pattern. Sets policy for rows with unexpected number of fields
+        pub fn field_count(mut self, policy: FieldCount) -> Self {
+            self.field_count = policy;
+            self
+        }
+
+        /// Part of Builder pattern. Sets protection against formula injection
+        pub fn sanitize(mut self, sanitize: Sanitize) -> Self {
+            self.sanitize = sanitize;
+            self
+        }
+
+        /// Part of Builder pattern. Allows numbers in the column with index `column` while sanitizing
+        pub fn allow_numbers(mut self, column: usize) -> Self {
+            self.allow_numbers.insert(column);
+            self
+        }
+
+        /// Part of Builder pattern. Sets size of the output buffer
+        pub fn buffer_capacity(mut self, capacity: usize) -> Self {
+            self.buffer_capacity = capacity;
+            self
+        }
+
+        /// Part of Builder pattern. Sets what to do with an error of flushing on drop
+        pub fn on_drop(mut self, policy: OnDrop) -> Self {
+            self.on_drop = policy;
+            self
+        }
+
+        /// Part of Builder pattern. Passes an error of flushing on drop to `sink`
+        pub fn report_on_drop(self, sink: impl FnMut(crate::Error) + Send + 'static) -> Self {
+            self.on_drop(OnDrop::Report(Box::new(sink)))
+        }
+
+        /// Quote style of the column with respect to overrides
+        pub(crate) fn quote_style_of(&self, column: usize) -> QuoteStyle {
+            self.column_quote_styles
+                .get(&column)
+                .copied()
+                .unwrap_or(self.quote_style)
+        }
     }
 
     impl Default for Config {
         fn default() -> Self {
             Self {
                 separator: String::from(","),
+                quote: '"',
                 escape: '"',
                 newline: NewLine::Rfc,
+                terminator: Terminator::Between,
+                encoding: encoding_rs::UTF_8,
+                bom: false,
+                quote_style: QuoteStyle::Necessary,
+                column_quote_styles: HashMap::new(),
+                field_count: FieldCount::Flexible,
+                sanitize: Sanitize::Off,
+                allow_numbers: HashSet::new(),
+                buffer_capacity: 8 * 1024,
+                on_drop: OnDrop::Stderr,
             }
         }
     }